@@ -0,0 +1,69 @@
+use crate::grapheme::Pos;
+
+#[allow(dead_code)]
+pub fn ease_linear(t: f64) -> f64 {
+    t
+}
+
+pub fn ease_in_quad(t: f64) -> f64 {
+    t * t
+}
+
+#[allow(dead_code)]
+pub fn ease_out_quad(t: f64) -> f64 {
+    -t * (t - 2.)
+}
+
+pub fn ease_out_cubic(t: f64) -> f64 {
+    let n = t - 1.;
+    n * n * n + 1.
+}
+
+pub fn ease_out_expo(t: f64) -> f64 {
+    if (t - 1.).abs() < f64::EPSILON {
+        1.
+    } else {
+        1. - 2f64.powf(-10. * t)
+    }
+}
+
+pub fn lerp(start: f64, end: f64, t: f64) -> f64 {
+    start + (end - start) * t
+}
+
+pub fn ease(ease_func: fn(f64) -> f64, start: f64, end: f64, t: f64) -> f64 {
+    lerp(start, end, ease_func(t))
+}
+
+pub fn ease_point(ease_func: fn(f64) -> f64, start: Pos, end: Pos, t: f64) -> Pos {
+    Pos::new(
+        ease(ease_func, start.x, end.x, t),
+        ease(ease_func, start.y, end.y, t),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lerp() {
+        assert_eq!(lerp(1., 0., 1.), 0.);
+        assert_eq!(lerp(0., 2., 0.5), 1.);
+    }
+
+    #[test]
+    fn test_ease_out_expo() {
+        assert_eq!(ease_out_expo(0.), 0.);
+        assert_eq!(ease_out_expo(1.), 1.);
+        assert!(ease_out_expo(0.5) > 0.5);
+    }
+
+    #[test]
+    fn test_ease_point() {
+        let start = Pos::new(0., 0.);
+        let end = Pos::new(2., 4.);
+        assert_eq!(ease_point(ease_linear, start, end, 0.5), Pos::new(1., 2.));
+        assert_eq!(ease_point(ease_out_cubic, start, end, 1.), end);
+    }
+}
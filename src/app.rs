@@ -1,6 +1,7 @@
 use std::cell::{Cell, RefCell};
 use std::rc::Rc;
 use std::sync::{atomic, Arc};
+use std::time::Duration;
 
 use gtk::gdk;
 use gtk::gdk::prelude::FontMapExt;
@@ -23,11 +24,19 @@ use crate::components::{VimCmdEvent, VimCmdPrompts};
 use crate::cursor::{CursorMode, VimCursor};
 use crate::event_aggregator::EVENT_AGGREGATOR;
 use crate::grapheme::Coord;
+use crate::guifont::GuiFont;
 use crate::keys::ToInput;
 use crate::metrics::Metrics;
-use crate::vimview::{self, VimGrid, VimMessage};
+use crate::minimap::{MinimapSettings, VimMinimap};
+use crate::scheduler::scheduler;
+use crate::settings::SETTINGS;
+use crate::vimview::{self, TextOptions, VimGrid, VimMessage};
 use crate::Opts;
 
+// default zindex of nvim floats and message grid.
+const FLOAT_ZINDEX: u64 = 50;
+const MESSAGES_ZINDEX: u64 = 200;
+
 #[allow(non_upper_case_globals)]
 pub static GridActived: Lazy<Arc<atomic::AtomicU64>> =
     Lazy::new(|| Arc::new(atomic::AtomicU64::new(0)));
@@ -38,6 +47,16 @@ pub enum AppMessage {
     ShowPointer,
     UiCommand(UiCommand),
     RedrawEvent(RedrawEvent),
+    // `w:rv_window_animation` of window on grid.
+    WindowAnimation { grid: u64, animatable: bool },
+    // external window on grid gets focus from window manager.
+    FocusWindow(u64),
+    // external window on grid asks to be closed.
+    CloseWindow(u64),
+    // float on grid dragged to editor cell {col}x{row}.
+    MoveWindow { grid: u64, col: u64, row: u64 },
+    // overlay scrollbar of grid dragged to show {top_line} on top.
+    ScrollWindow { grid: u64, top_line: u64 },
 }
 
 impl From<UiCommand> for AppMessage {
@@ -57,11 +76,15 @@ pub struct AppModel {
     pub guifont: Option<String>,
     pub guifontset: Option<String>,
     pub guifontwide: Option<String>,
+    // guifontwide, ambiwidth, emoji and arabicshape, shared with textbufs.
+    pub text_options: Rc<RefCell<TextOptions>>,
     pub metrics: Rc<Cell<Metrics>>,
     pub show_tab_line: Option<u64>,
 
     pub font_description: Rc<RefCell<pango::FontDescription>>,
     pub font_changed: Rc<atomic::AtomicBool>,
+    // pixels added to width of cells, `:w` of guifont.
+    pub font_width_offset: f64,
 
     pub mode: EditorMode,
 
@@ -72,6 +95,10 @@ pub struct AppModel {
     pub cursor_coord_changed: atomic::AtomicBool,
     pub cursor_mode: usize,
     pub cursor_modes: Vec<CursorMode>,
+    // moves cursor into toplevels of external windows.
+    pub cursor_host: vimview::CursorHost,
+
+    pub minimap: MicroComponent<VimMinimap>,
 
     pub pctx: Rc<pango::Context>,
     pub gtksettings: OnceCell<gtk::Settings>,
@@ -86,6 +113,8 @@ pub struct AppModel {
     pub messages: FactoryVec<vimview::VimMessage>,
 
     pub dragging: Rc<Cell<Option<Dragging>>>,
+    // increase on every float positioned.
+    pub float_order: u64,
     pub show_pointer: atomic::AtomicBool,
 
     pub rt: tokio::runtime::Runtime,
@@ -114,12 +143,7 @@ impl AppModel {
                 ctx.set_font_description(&font_desc);
                 ctx.set_base_dir(pango::Direction::Ltr);
                 ctx.set_language(&pango::Language::from_string("en-US"));
-                let mut options = cairo::FontOptions::new().ok();
-                options.as_mut().map(|options| {
-                    // options.set_hint_style(cairo::HintStyle::Full);
-                    // options.set_antialias(cairo::Antialias::Subpixel);
-                    options.set_hint_metrics(cairo::HintMetrics::On);
-                });
+                let options = SETTINGS.get::<vimview::FontSettings>().font_options();
                 pangocairo::context_set_font_options(&ctx, options.as_ref());
                 ctx
             })
@@ -127,6 +151,11 @@ impl AppModel {
             .into();
         let hldefs = Rc::new(RwLock::new(vimview::HighlightDefinitions::new()));
         let metrics = Rc::new(Metrics::new().into());
+        let cursor = MicroComponent::new(
+            VimCursor::new(pctx.clone(), Rc::clone(&metrics), hldefs.clone()),
+            (),
+        );
+        let cursor_host = vimview::CursorHost::new(&cursor.root_widget());
         AppModel {
             size,
             title: opts.title.clone(),
@@ -135,21 +164,22 @@ impl AppModel {
             guifont: None,
             guifontset: None,
             guifontwide: None,
+            text_options: Rc::new(RefCell::new(TextOptions::default())),
             show_tab_line: None,
 
             mode: EditorMode::Normal,
 
             mouse_on: Rc::new(false.into()),
-            cursor: MicroComponent::new(
-                VimCursor::new(pctx.clone(), Rc::clone(&metrics), hldefs.clone()),
-                (),
-            ),
+            cursor,
+            cursor_host,
             cursor_grid: 0,
             cursor_mode: 0,
             cursor_modes: Vec::new(),
             cursor_coord: Coord::default(),
             cursor_coord_changed: atomic::AtomicBool::new(false),
 
+            minimap: MicroComponent::new(VimMinimap::new(hldefs.clone()), ()),
+
             pctx,
             gtksettings: OnceCell::new(),
             im_context: OnceCell::new(),
@@ -157,6 +187,7 @@ impl AppModel {
             metrics,
             font_description: Rc::new(RefCell::new(font_desc)),
             font_changed: Rc::new(false.into()),
+            font_width_offset: 0.,
 
             hldefs,
             hlgroups: Rc::new(RwLock::new(FxHashMap::default())),
@@ -167,6 +198,7 @@ impl AppModel {
             messages: FactoryVec::new(),
 
             dragging: Rc::new(Cell::new(None)),
+            float_order: 0,
             show_pointer: true.into(),
 
             opts,
@@ -175,6 +207,25 @@ impl AppModel {
         }
     }
 
+    /// Font options of settings, edging and hinting of guifont take
+    /// precedence.
+    fn set_font_options(&self) {
+        let mut options = SETTINGS.get::<vimview::FontSettings>().font_options();
+        let font = self
+            .guifont
+            .as_deref()
+            .and_then(|guifont| GuiFont::parse(guifont).ok());
+        if let (Some(options), Some(font)) = (options.as_mut(), font) {
+            if let Some(edging) = font.edging {
+                options.set_antialias(edging.into());
+            }
+            if let Some(hinting) = font.hinting {
+                options.set_hint_style(hinting.into());
+            }
+        }
+        pangocairo::context_set_font_options(&self.pctx, options.as_ref());
+    }
+
     pub fn calculate(&self) {
         const PANGO_SCALE: f64 = pango::SCALE as f64;
         const SINGLE_WIDTH_CHARS: &'static str = concat!(
@@ -220,9 +271,14 @@ impl AppModel {
         log::info!("font-metrics width: {}", fm_width as f64 / PANGO_SCALE);
         log::info!("font-metrics height: {}", fm_height as f64 / PANGO_SCALE);
         log::info!("font-metrics ascent: {}", fm_ascent as f64 / PANGO_SCALE);
+        // pango gives position of top of underline above baseline.
+        let underline_position = (-font_metrics.underline_position() as f64 / PANGO_SCALE).round();
+        let underline_thickness = (font_metrics.underline_thickness() as f64 / PANGO_SCALE)
+            .round()
+            .max(1.);
         let mut metrics = self.metrics.get();
         let charwidth = max_width as f64 / PANGO_SCALE;
-        let width = charwidth;
+        let width = (charwidth + self.font_width_offset).max(1.);
         let charheight = if fm_height > 0 {
             fm_height.min(max_height) as f64 / PANGO_SCALE
         } else {
@@ -231,9 +287,13 @@ impl AppModel {
         if metrics.charheight() == charheight
             && metrics.charwidth() == charwidth
             && metrics.width() == width
+            && metrics.underline_position() == underline_position
+            && metrics.underline_thickness() == underline_thickness
         {
             return;
         }
+        metrics.set_underline_position(underline_position);
+        metrics.set_underline_thickness(underline_thickness);
         metrics.set_width(width.ceil());
         metrics.set_ascent(ascent.ceil());
         metrics.set_charwidth(charwidth.ceil());
@@ -245,6 +305,44 @@ impl AppModel {
     }
 }
 
+impl AppModel {
+    /// Show minimap of the window of cursor grid, attaching its buffer.
+    /// Floats and external windows keep the last window shown.
+    fn follow_minimap(&mut self) {
+        let enabled = SETTINGS.get::<MinimapSettings>().enabled;
+        let grid = self.cursor_grid;
+        let target = self
+            .vgrids
+            .get(grid)
+            .filter(|vgrid| !vgrid.is_float() && !vgrid.is_external())
+            .and_then(|vgrid| Some((vgrid.window()?, vgrid.viewport()?)));
+        let mut minimap = self.minimap.model_mut().unwrap();
+        match target {
+            Some((window, (top, bottom, _))) if enabled => {
+                minimap.set_viewport(top, bottom);
+                if minimap.watch(grid, window.get_value()) {
+                    EVENT_AGGREGATOR.send(UiCommand::Parallel(ParallelCommand::WatchBuffer {
+                        grid,
+                        window: window.get_value().clone(),
+                        current: minimap.buffer(),
+                    }));
+                }
+            }
+            _ if enabled => {}
+            _ => {
+                minimap.hide();
+                if let Some(buffer) = minimap.unwatch() {
+                    EVENT_AGGREGATOR.send(UiCommand::Parallel(ParallelCommand::DetachBuffer {
+                        buffer,
+                    }));
+                }
+            }
+        }
+        drop(minimap);
+        self.minimap.update_view().unwrap();
+    }
+}
+
 impl Model for AppModel {
     type Msg = AppMessage;
     type Widgets = AppWidgets;
@@ -261,6 +359,18 @@ impl AppUpdate for AppModel {
         match message {
             AppMessage::UiCommand(ui_command) => {
                 log::trace!("ui-commad {:?}", ui_command);
+                let focused = match ui_command {
+                    UiCommand::Parallel(ParallelCommand::FocusGained) => Some(true),
+                    UiCommand::Parallel(ParallelCommand::FocusLost) => Some(false),
+                    _ => None,
+                };
+                if let Some(focused) = focused {
+                    self.cursor
+                        .model_mut()
+                        .map(|mut m| m.set_focused(focused))
+                        .unwrap();
+                    self.cursor.update_view().unwrap();
+                }
                 EVENT_AGGREGATOR.send(ui_command);
             }
             AppMessage::Quit => {
@@ -269,6 +379,52 @@ impl AppUpdate for AppModel {
             AppMessage::ShowPointer => {
                 self.show_pointer.store(true, atomic::Ordering::Relaxed);
             }
+            AppMessage::WindowAnimation { grid, animatable } => {
+                if let Some(vgrid) = self.vgrids.get_mut(grid) {
+                    vgrid.set_animatable(animatable);
+                }
+            }
+            AppMessage::FocusWindow(grid) => {
+                if let Some(window) = self.vgrids.get(grid).and_then(|vgrid| vgrid.window()) {
+                    EVENT_AGGREGATOR.send(UiCommand::Parallel(ParallelCommand::FocusWindow {
+                        window: window.get_value().clone(),
+                    }));
+                }
+            }
+            AppMessage::CloseWindow(grid) => {
+                if let Some(window) = self.vgrids.get(grid).and_then(|vgrid| vgrid.window()) {
+                    let window = window.clone();
+                    self.rt.spawn(async move {
+                        if let Err(err) = window.close(false).await {
+                            log::warn!("failed to close window of grid {}: {}", grid, err);
+                        }
+                    });
+                }
+            }
+            AppMessage::MoveWindow { grid, col, row } => {
+                if let Some(window) = self.vgrids.get(grid).and_then(|vgrid| vgrid.window()) {
+                    let window = window.clone();
+                    let config = vec![
+                        ("relative".into(), "editor".into()),
+                        ("anchor".into(), "NW".into()),
+                        ("row".into(), row.into()),
+                        ("col".into(), col.into()),
+                    ];
+                    self.rt.spawn(async move {
+                        if let Err(err) = window.set_config(config).await {
+                            log::warn!("failed to move float of grid {}: {}", grid, err);
+                        }
+                    });
+                }
+            }
+            AppMessage::ScrollWindow { grid, top_line } => {
+                if let Some(window) = self.vgrids.get(grid).and_then(|vgrid| vgrid.window()) {
+                    EVENT_AGGREGATOR.send(UiCommand::Parallel(ParallelCommand::ScrollWindow {
+                        window: window.get_value().clone(),
+                        top_line,
+                    }));
+                }
+            }
             AppMessage::RedrawEvent(event) => {
                 match event {
                     RedrawEvent::SetTitle { title } => {
@@ -280,27 +436,49 @@ impl AppUpdate for AppModel {
                     }
                     RedrawEvent::OptionSet { gui_option } => match gui_option {
                         bridge::GuiOption::AmbiWidth(ambi_width) => {
-                            log::debug!("unhandled ambi_width {}", ambi_width);
+                            log::debug!("ambi_width {}", ambi_width);
+                            // nvim redraws grids with cells of new widths.
+                            self.text_options.borrow_mut().ambiwidth_double =
+                                ambi_width == "double";
                         }
                         bridge::GuiOption::ArabicShape(arabic_shape) => {
-                            log::debug!("unhandled arabic-shape: {}", arabic_shape);
+                            log::debug!("arabic-shape: {}", arabic_shape);
+                            self.text_options.borrow_mut().arabicshape = arabic_shape;
+                            self.vgrids
+                                .iter_mut()
+                                .for_each(|(_, vgrid)| vgrid.reset_cache());
                         }
                         bridge::GuiOption::Emoji(emoji) => {
                             log::debug!("emoji: {}", emoji);
+                            // presentation changes with cells redrawn by nvim.
+                            self.text_options.borrow_mut().emoji = emoji;
                         }
                         bridge::GuiOption::GuiFont(guifont) => {
-                            if !guifont.trim().is_empty() {
-                                log::info!("gui font: {}", &guifont);
-                                let desc = pango::FontDescription::from_string(
-                                    &guifont.replace(":h", " "),
-                                );
+                            let font = match GuiFont::parse(&guifont) {
+                                Ok(font) => Some(font),
+                                Err(err) => {
+                                    log::error!("invalid guifont '{}': {}", guifont, err);
+                                    EVENT_AGGREGATOR.send(UiCommand::Parallel(
+                                        ParallelCommand::ShowError(format!(
+                                            "Invalid guifont '{}': {}",
+                                            guifont, err
+                                        )),
+                                    ));
+                                    None
+                                }
+                            };
+                            if let Some(font) = font.filter(|_| !guifont.trim().is_empty()) {
+                                log::info!("gui font: {} {:?}", &guifont, font);
+                                let desc = font.description(&self.font_description.borrow());
 
                                 self.pctx.set_font_description(&desc);
+                                self.guifont.replace(guifont);
+                                self.set_font_options();
+                                self.font_width_offset = font.width.unwrap_or(0.);
                                 self.gtksettings.get().map(|settings| {
                                     settings.set_gtk_font_name(Some(&desc.to_str()));
                                 });
 
-                                self.guifont.replace(guifont);
                                 self.font_description.replace(desc);
 
                                 self.calculate();
@@ -318,7 +496,29 @@ impl AppUpdate for AppModel {
                             self.guifontset.replace(guifontset);
                         }
                         bridge::GuiOption::GuiFontWide(guifontwide) => {
-                            self.guifontwide.replace(guifontwide);
+                            let font = match GuiFont::parse(&guifontwide) {
+                                Ok(font) => Some(font),
+                                Err(err) => {
+                                    log::error!("invalid guifontwide '{}': {}", guifontwide, err);
+                                    EVENT_AGGREGATOR.send(UiCommand::Parallel(
+                                        ParallelCommand::ShowError(format!(
+                                            "Invalid guifontwide '{}': {}",
+                                            guifontwide, err
+                                        )),
+                                    ));
+                                    None
+                                }
+                            };
+                            if let Some(font) = font {
+                                log::info!("gui font wide: {} {:?}", &guifontwide, font);
+                                // empty option falls back to guifont.
+                                let font = Some(font).filter(|font| *font != GuiFont::default());
+                                self.text_options.borrow_mut().font_wide = font;
+                                self.guifontwide.replace(guifontwide);
+                                self.vgrids
+                                    .iter_mut()
+                                    .for_each(|(_, vgrid)| vgrid.reset_cache());
+                            }
                         }
                         bridge::GuiOption::LineSpace(linespace) => {
                             log::info!("line space: {}", linespace);
@@ -344,11 +544,37 @@ impl AppUpdate for AppModel {
                             .store(true, atomic::Ordering::Relaxed);
                         self.hldefs.write().set_defaults(colors);
                     }
-                    RedrawEvent::HighlightAttributesDefine { id, style } => {
-                        self.hldefs.write().set(id, style);
+                    RedrawEvent::HighlightAttributesDefine {
+                        id,
+                        style,
+                        mut groups,
+                    } => {
+                        // ui groups are only known from hl_group_set.
+                        groups.extend(
+                            self.hlgroups
+                                .read()
+                                .iter()
+                                .filter(|(name, group)| **group == id && !groups.contains(name))
+                                .map(|(name, _)| name.clone())
+                                .collect::<Vec<_>>(),
+                        );
+                        let hldefs = self.hldefs.write();
+                        hldefs.set(id, style);
+                        hldefs.set_groups(id, groups);
                     }
                     RedrawEvent::HighlightGroupSet { name, id } => {
-                        self.hlgroups.write().insert(name, id);
+                        let hldefs = self.hldefs.write();
+                        let previous = self.hlgroups.write().insert(name.clone(), id);
+                        if let Some(previous) = previous.filter(|previous| *previous != id) {
+                            let mut groups = hldefs.groups(previous).unwrap_or_default().to_vec();
+                            groups.retain(|group| *group != name);
+                            hldefs.set_groups(previous, groups);
+                        }
+                        let mut groups = hldefs.groups(id).unwrap_or_default().to_vec();
+                        if !groups.contains(&name) {
+                            groups.push(name);
+                            hldefs.set_groups(id, groups);
+                        }
                         log::trace!("current highlight groups: {:?}", self.hlgroups.read());
                     }
                     RedrawEvent::Clear { grid } => {
@@ -405,41 +631,41 @@ impl AppUpdate for AppModel {
                     }
                     RedrawEvent::Scroll {
                         grid,
-                        top: _,
-                        bottom: _,
-                        left: _,
-                        right: _,
+                        top,
+                        bottom,
+                        left,
+                        right,
                         rows,
                         columns,
                     } => {
                         let vgrid = self.vgrids.get_mut(grid).unwrap();
-                        if rows.is_positive() {
-                            vgrid.up(rows.abs() as _);
-                        } else if rows.is_negative() {
-                            vgrid.down(rows.abs() as _);
-                        } else if columns.is_positive() {
-                            unimplemented!("scroll left.");
-                        } else if columns.is_negative() {
-                            unimplemented!("scroll right.");
-                        } else {
-                            // rows and columns are both zero.
-                            unimplemented!("could not be there.");
+                        if rows == 0 && columns == 0 {
+                            log::warn!("grid {} scrolled nothing.", grid);
                         }
+                        vgrid.scroll(
+                            top as _,
+                            bottom as _,
+                            left as _,
+                            right as _,
+                            rows as _,
+                            columns as _,
+                        );
                         let cursor_grid = self.cursor_grid;
                         log::debug!("scrolling grid {} cursor at {}", grid, cursor_grid);
                         if cursor_grid == grid {
                             let coord = &self.cursor_coord;
-                            let cell = vgrid
+                            if let Some(cell) = vgrid
                                 .textbuf()
                                 .borrow()
                                 .cell((coord.row).floor() as usize, (coord.col).floor() as usize)
-                                .unwrap();
-                            log::debug!("cursor character change to {}", cell.text);
-                            self.cursor
-                                .model_mut()
-                                .map(|mut m| m.set_cell(cell))
-                                .unwrap();
-                            self.cursor.update_view().unwrap();
+                            {
+                                log::debug!("cursor character change to {}", cell.text);
+                                self.cursor
+                                    .model_mut()
+                                    .map(|mut m| m.set_cell(cell))
+                                    .unwrap();
+                                self.cursor.update_view().unwrap();
+                            }
                         }
                     }
                     RedrawEvent::Resize {
@@ -457,7 +683,7 @@ impl AppUpdate for AppModel {
                                 .resize(width as _, height as _);
                         } else {
                             log::debug!("Add grid {} to default window at left top.", grid);
-                            let vgrid = VimGrid::new(
+                            let mut vgrid = VimGrid::new(
                                 grid,
                                 0,
                                 (0., 0.).into(),
@@ -468,13 +694,15 @@ impl AppUpdate for AppModel {
                                 self.font_description.clone(),
                             );
                             vgrid.set_pango_context(self.pctx.clone());
+                            vgrid.set_text_options(self.text_options.clone());
+                            vgrid.set_cursor_host(self.cursor_host.clone());
                             self.vgrids.insert(grid, vgrid);
                         };
                     }
 
                     RedrawEvent::WindowPosition {
                         grid,
-                        window: _,
+                        window,
                         start_row: row,
                         start_column: column,
                         width,
@@ -486,7 +714,7 @@ impl AppUpdate for AppModel {
 
                         if self.vgrids.get(grid).is_none() {
                             // dose not exists, create
-                            let vgrid = VimGrid::new(
+                            let mut vgrid = VimGrid::new(
                                 grid,
                                 0,
                                 (column as usize, row as usize).into(),
@@ -496,7 +724,10 @@ impl AppUpdate for AppModel {
                                 self.metrics.clone(),
                                 self.font_description.clone(),
                             );
+                            vgrid.set_window(window);
                             vgrid.set_pango_context(self.pctx.clone());
+                            vgrid.set_text_options(self.text_options.clone());
+                            vgrid.set_cursor_host(self.cursor_host.clone());
                             self.vgrids.insert(grid, vgrid);
                             log::info!(
                                 "Add grid {} at {}x{} with {}x{}.",
@@ -510,6 +741,12 @@ impl AppUpdate for AppModel {
                             let vgrid = self.vgrids.get_mut(grid).unwrap();
                             vgrid.resize(width as _, height as _);
                             vgrid.set_coord(column as _, row as _);
+                            // float turned into a normal window.
+                            vgrid.set_is_float(false);
+                            vgrid.set_focusable(true);
+                            vgrid.set_zindex(0, 0);
+                            vgrid.set_window(window);
+                            vgrid.set_external(false);
                             log::debug!(
                                 "Move grid {} to {}x{} with {}x{}.",
                                 grid,
@@ -526,9 +763,21 @@ impl AppUpdate for AppModel {
                             grid, row, column, width, height,
                         );
                     }
+                    RedrawEvent::WindowExternalPosition { grid, window } => {
+                        log::info!("grid {} is external window", grid);
+                        if let Some(vgrid) = self.vgrids.get_mut(grid) {
+                            vgrid.set_window(window);
+                            vgrid.set_external(true);
+                            vgrid.set_is_float(false);
+                            vgrid.set_zindex(0, 0);
+                            vgrid.show();
+                        } else {
+                            log::warn!("WindowExternalPosition before create grid {}.", grid);
+                        }
+                    }
                     RedrawEvent::WindowViewport {
                         grid,
-                        window: _,
+                        window,
                         top_line,
                         bottom_line,
                         current_line,
@@ -544,7 +793,12 @@ impl AppUpdate for AppModel {
                             log::warn!("WindowViewport before create grid {}.", grid);
                         } else {
                             let vgrid = self.vgrids.get_mut(grid).unwrap();
+                            vgrid.set_window(window);
+                            vgrid.set_viewport(top_line, bottom_line, line_count);
                             vgrid.show();
+                            if grid == self.cursor_grid {
+                                self.follow_minimap();
+                            }
                         }
                     }
                     RedrawEvent::WindowHide { grid } => {
@@ -563,8 +817,15 @@ impl AppUpdate for AppModel {
                         self.vgrids.flush();
                     }
                     RedrawEvent::CursorGoto { grid, row, column } => {
+                        let previous_grid = self.cursor_grid;
+                        let grid_changed = previous_grid != grid;
                         let vgrid = self.vgrids.get(grid).unwrap();
-                        let leftop = vgrid.coord();
+                        // cursor is drawn in the toplevel of external window.
+                        let leftop = if vgrid.is_external() {
+                            Coord::default()
+                        } else {
+                            vgrid.coord().clone()
+                        };
                         let row = row as usize;
                         let column = column as usize;
                         if let Some(cell) = vgrid.textbuf().borrow().cell(row, column) {
@@ -600,7 +861,18 @@ impl AppUpdate for AppModel {
                         }
                         self.cursor_coord_changed
                             .store(true, atomic::Ordering::Relaxed);
+                        if grid_changed {
+                            if let Some(vgrid) = self.vgrids.get_mut(previous_grid) {
+                                vgrid.set_cursor(None);
+                            }
+                        }
+                        if let Some(vgrid) = self.vgrids.get_mut(grid) {
+                            vgrid.set_cursor(Some((row, column)));
+                        }
                         self.cursor_grid = grid;
+                        if grid_changed {
+                            self.follow_minimap();
+                        }
                     }
                     RedrawEvent::ModeInfoSet { cursor_modes } => {
                         self.cursor_modes = cursor_modes;
@@ -609,20 +881,37 @@ impl AppUpdate for AppModel {
                         self.cursor
                             .model_mut()
                             .map(|mut m| {
-                                m.set_mode(mode);
+                                m.change_mode(&mode);
                             })
                             .unwrap();
                         self.cursor.update_view().unwrap();
                     }
+                    RedrawEvent::LangmapChange { enabled } => {
+                        // reported on every typed char, most are no change.
+                        let changed = self
+                            .cursor
+                            .model_mut()
+                            .map(|mut m| {
+                                let changed = m.langmap != enabled;
+                                m.set_langmap(enabled);
+                                changed
+                            })
+                            .unwrap();
+                        if changed {
+                            self.cursor.update_view().unwrap();
+                        }
+                    }
                     RedrawEvent::ModeChange { mode, mode_index } => {
                         self.mode = mode;
                         self.cursor_mode = mode_index as _;
                         let cursor_mode = self.cursor_modes.get(self.cursor_mode).unwrap().clone();
                         log::info!("Mode Change to {:?} {:?}", &self.mode, cursor_mode);
+                        let editor_mode = self.mode.clone();
                         self.cursor
                             .model_mut()
                             .map(|mut m| {
-                                m.set_mode(cursor_mode);
+                                m.change_mode(&cursor_mode);
+                                m.set_editor_mode(editor_mode);
                             })
                             .unwrap();
                         self.cursor.update_view().unwrap();
@@ -661,6 +950,7 @@ impl AppUpdate for AppModel {
                             self.hldefs.clone(),
                             self.metrics.clone(),
                             self.pctx.clone(),
+                            self.text_options.clone(),
                         ))
                     }
                     RedrawEvent::MessageShowMode { content } => {
@@ -694,6 +984,7 @@ impl AppUpdate for AppModel {
                             );
                             vgrid.set_coord(0., row as f64);
                             vgrid.resize(width, vgrid.height());
+                            vgrid.set_zindex(MESSAGES_ZINDEX, 0);
                             vgrid.show();
                         } else {
                             log::debug!("creating message grid at 0x{} size {}x{}", row, width, 1);
@@ -708,8 +999,11 @@ impl AppUpdate for AppModel {
                                 self.metrics.clone(),
                                 self.font_description.clone(),
                             );
+                            vgrid.set_zindex(MESSAGES_ZINDEX, 0);
                             vgrid.show();
                             vgrid.set_pango_context(self.pctx.clone());
+                            vgrid.set_text_options(self.text_options.clone());
+                            vgrid.set_cursor_host(self.cursor_host.clone());
                             self.vgrids.insert(grid, vgrid);
                         }
                     }
@@ -726,12 +1020,13 @@ impl AppUpdate for AppModel {
 
                     RedrawEvent::WindowFloatPosition {
                         grid,
+                        window,
                         anchor,
                         anchor_grid,
                         anchor_row,
                         anchor_column,
                         focusable,
-                        sort_order: _,
+                        sort_order,
                     } => {
                         log::info!(
                             "grid {} is float window exists in vgrids {} anchor {} {:?} pos {}x{} focusable {}",
@@ -771,10 +1066,96 @@ impl AppUpdate for AppModel {
                         // let y = row * metrics.height();
                         log::info!("moving float window {} to {}x{}", grid, col, row);
                         vgrid.set_coord(coord.col + col.max(0.), coord.row + row.max(0.));
+                        // repositions of an existing float keep its settings.
+                        let created = !vgrid.is_float();
                         vgrid.set_is_float(true);
                         vgrid.set_focusable(focusable);
+                        vgrid.set_window(window.clone());
+                        vgrid.set_external(false);
+                        // floats stay above normal windows, latest on top of same zindex.
+                        let zindex = sort_order.unwrap_or(FLOAT_ZINDEX).max(1);
+                        if created || vgrid.zindex() != zindex {
+                            self.float_order += 1;
+                            vgrid.set_zindex(zindex, self.float_order);
+                        }
+
+                        // read once per float, reply takes effect since next move of the float.
+                        if created {
+                            let sender = sender.clone();
+                            self.rt.spawn(async move {
+                                let animatable = window
+                                    .get_var("rv_window_animation")
+                                    .await
+                                    .ok()
+                                    .and_then(|v| v.as_bool())
+                                    .unwrap_or(true);
+                                sender
+                                    .send(AppMessage::WindowAnimation { grid, animatable })
+                                    .ok();
+                            });
+                        }
                     }
 
+                    RedrawEvent::BufferAttach {
+                        grid,
+                        window,
+                        buffer,
+                    } => {
+                        log::debug!("minimap attaching buffer of grid {}", grid);
+                        self.minimap.model_mut().unwrap().set_buffer(window, buffer);
+                        // cursor may have left the window, or minimap been disabled, meanwhile.
+                        self.follow_minimap();
+                    }
+                    RedrawEvent::BufferWatched { grid, window } => {
+                        self.minimap.model_mut().unwrap().set_watched(grid, window);
+                        self.follow_minimap();
+                    }
+                    RedrawEvent::BufferDetach { buffer } => {
+                        self.minimap.model_mut().unwrap().set_detached(&buffer);
+                        self.follow_minimap();
+                    }
+                    RedrawEvent::BufferWindowEnter => {
+                        self.minimap.model_mut().unwrap().rewatch();
+                        self.follow_minimap();
+                    }
+                    RedrawEvent::BufferLines {
+                        buffer,
+                        first_line,
+                        last_line,
+                        lines,
+                    } => {
+                        let mut minimap = self.minimap.model_mut().unwrap();
+                        if minimap.set_lines(&buffer, first_line, last_line, lines) {
+                            minimap.refresh_highlights();
+                            drop(minimap);
+                            self.minimap.update_view().unwrap();
+                        }
+                    }
+                    RedrawEvent::BufferHighlights { buffer, highlights } => {
+                        self.minimap
+                            .model_mut()
+                            .unwrap()
+                            .set_highlights(&buffer, highlights);
+                        self.minimap.update_view().unwrap();
+                    }
+                    RedrawEvent::SettingChanged { name } => {
+                        log::debug!("setting {} changed", name);
+                        if name.starts_with("minimap_") {
+                            self.follow_minimap();
+                        }
+                        if let Some(name) = name.strip_prefix("font_") {
+                            if vimview::FontSettings::FONT_OPTIONS.contains(&name) {
+                                self.set_font_options();
+                                self.calculate();
+                                self.font_changed.store(true, atomic::Ordering::Relaxed);
+                                self.cursor_coord_changed
+                                    .store(true, atomic::Ordering::Relaxed);
+                            }
+                            self.vgrids
+                                .iter_mut()
+                                .for_each(|(_, vgrid)| vgrid.reset_cache());
+                        }
+                    }
                     RedrawEvent::CommandLineShow {
                         content,
                         position,
@@ -838,75 +1219,78 @@ impl Widgets<AppModel, ()> for AppWidgets {
 
                 // set_child: Add tabline
 
-                append: overlay = &gtk::Overlay {
-                    set_focusable: true,
-                    set_sensitive: true,
-                    set_can_focus: true,
-                    set_can_target: true,
-                    set_focus_on_click: true,
-                    set_child: da = Some(&gtk::DrawingArea) {
+                append: hbox = &gtk::Box {
+                    set_orientation: gtk::Orientation::Horizontal,
+                    set_spacing: 0,
+                    set_hexpand: true,
+                    set_vexpand: true,
+                    append: overlay = &gtk::Overlay {
                         set_hexpand: true,
-                        set_vexpand: true,
-                        set_focus_on_click: false,
-                        set_overflow: gtk::Overflow::Hidden,
-                        connect_resize[sender = sender.clone(), metrics = model.metrics.clone(), size = model.size.clone()] => move |da, width, height| {
-                            log::debug!("da resizing width: {}, height: {}", width, height);
-                            size.set((width, height));
-                            let metrics = metrics.get();
-                            let rows = da.height() as f64 / metrics.height(); //  + metrics.linespace
-                            let cols = da.width() as f64 / metrics.width();
-                            log::debug!("da resizing rows: {} cols: {}", rows, cols);
-                            sender
-                                .send(
-                                    UiCommand::Parallel(ParallelCommand::Resize {
-                                        width: cols as _,
-                                        height: rows as _,
-                                    })
-                                    .into(),
-                                )
-                                .unwrap();
-                        },
-                        set_draw_func[hldefs = model.hldefs.clone()] => move |_da, cr, w, h| {
-                            let hldefs = hldefs.read();
-                            let default_colors = hldefs.defaults().unwrap();
-                            log::debug!("drawing default background {}x{}.", w, h);
-                            if let Some(bg) = default_colors.background {
-                                cr.rectangle(0., 0., w.into(), h.into());
-                                cr.set_source_rgb(bg.red() as _, bg.green() as _, bg.blue() as _);
-                                cr.paint().unwrap();
-                            }
-                        }
-                    },
-                    add_overlay: grids_container = &gtk::Fixed {
-                        set_widget_name: "grids-container",
-                        set_visible: true,
+                        set_focusable: true,
+                        set_sensitive: true,
+                        set_can_focus: true,
+                        set_can_target: true,
                         set_focus_on_click: true,
-                        factory!(model.vgrids),
-                    },
-                    add_overlay: float_win_container = &gtk::Fixed {
-                        set_widget_name: "float-win-container",
-                        set_visible: false,
-                        set_hexpand: false,
-                        set_vexpand: false,
-                    },
-                    add_overlay: model.cursor.root_widget(),
-                    add_overlay: messages_container = &gtk::Box {
-                        set_widget_name: "messages-container",
-                        set_opacity: 0.95,
-                        set_spacing: 5,
-                        set_visible: false,
-                        set_hexpand: true,
-                        // It dosenot matter.
-                        set_width_request: 0,
-                        set_homogeneous: false,
-                        set_focus_on_click: false,
-                        set_halign: gtk::Align::End,
-                        set_valign: gtk::Align::Start,
-                        set_overflow: gtk::Overflow::Visible,
-                        set_orientation: gtk::Orientation::Vertical,
-                        factory!(model.messages),
+                        set_child: da = Some(&gtk::DrawingArea) {
+                            set_hexpand: true,
+                            set_vexpand: true,
+                            set_focus_on_click: false,
+                            set_overflow: gtk::Overflow::Hidden,
+                            connect_resize[sender = sender.clone(), metrics = model.metrics.clone(), size = model.size.clone()] => move |da, width, height| {
+                                log::debug!("da resizing width: {}, height: {}", width, height);
+                                size.set((width, height));
+                                let metrics = metrics.get();
+                                let rows = da.height() as f64 / metrics.height(); //  + metrics.linespace
+                                let cols = da.width() as f64 / metrics.width();
+                                log::debug!("da resizing rows: {} cols: {}", rows, cols);
+                                sender
+                                    .send(
+                                        UiCommand::Parallel(ParallelCommand::Resize {
+                                            width: cols as _,
+                                            height: rows as _,
+                                        })
+                                        .into(),
+                                    )
+                                    .unwrap();
+                            },
+                            set_draw_func[hldefs = model.hldefs.clone()] => move |_da, cr, w, h| {
+                                let hldefs = hldefs.read();
+                                let default_colors = hldefs.defaults().unwrap();
+                                log::debug!("drawing default background {}x{}.", w, h);
+                                if let Some(bg) = default_colors.background {
+                                    cr.rectangle(0., 0., w.into(), h.into());
+                                    cr.set_source_rgb(bg.red() as _, bg.green() as _, bg.blue() as _);
+                                    cr.paint().unwrap();
+                                }
+                            }
+                        },
+                        add_overlay: grids_container = &gtk::Fixed {
+                            set_widget_name: "grids-container",
+                            set_visible: true,
+                            set_focus_on_click: true,
+                            factory!(model.vgrids),
+                        },
+                        add_overlay: model.cursor.root_widget(),
+                        add_overlay: messages_container = &gtk::Box {
+                            set_widget_name: "messages-container",
+                            set_opacity: 0.95,
+                            set_spacing: 5,
+                            set_visible: false,
+                            set_hexpand: true,
+                            // It dosenot matter.
+                            set_width_request: 0,
+                            set_homogeneous: false,
+                            set_focus_on_click: false,
+                            set_halign: gtk::Align::End,
+                            set_valign: gtk::Align::Start,
+                            set_overflow: gtk::Overflow::Visible,
+                            set_orientation: gtk::Orientation::Vertical,
+                            factory!(model.messages),
+                        },
+                        // add_overlay: components.cmd_prompt.root_widget() ,
                     },
-                    // add_overlay: components.cmd_prompt.root_widget() ,
+                    // sidebar beside grids, nvim gets the width left.
+                    append: model.minimap.root_widget(),
                 }
             },
             connect_close_request[sender = sender.clone()] => move |_| {
@@ -916,10 +1300,6 @@ impl Widgets<AppModel, ()> for AppWidgets {
         }
     }
 
-    additional_fields! {
-        pointer_animation: adw::TimedAnimation,
-    }
-
     fn post_init() {
         model.calculate();
         model.gtksettings.set(overlay.settings()).ok();
@@ -933,17 +1313,7 @@ impl Widgets<AppModel, ()> for AppWidgets {
         da.queue_resize();
         da.queue_draw();
 
-        let target = adw::CallbackAnimationTarget::new(Some(Box::new(
-            glib::clone!(@weak main_window => move |_| {
-                main_window.set_cursor_from_name(Some("text"));
-            }),
-        )));
-        let pointer_animation = adw::TimedAnimation::new(&main_window, 0., 1., 1000, &target);
-        pointer_animation.set_easing(adw::Easing::Linear);
-        pointer_animation.set_repeat_count(1);
-        pointer_animation.connect_done(move |this| {
-            this.widget().set_cursor_from_name(Some("none"));
-        });
+        scheduler().attach(main_window.upcast_ref());
 
         let im_context = gtk::IMMulticontext::new();
         im_context.set_use_preedit(false);
@@ -1062,7 +1432,16 @@ impl Widgets<AppModel, ()> for AppWidgets {
             atomic::Ordering::Acquire,
             atomic::Ordering::Relaxed,
         ) {
-            self.pointer_animation.play();
+            let main_window = &self.main_window;
+            main_window.set_cursor_from_name(Some("text"));
+            scheduler().timeout(
+                "pointer-hide",
+                Duration::from_secs(1),
+                glib::clone!(@weak main_window => @default-return None, move || {
+                    main_window.set_cursor_from_name(Some("none"));
+                    None
+                }),
+            );
         }
         if let Ok(true) = model.background_changed.compare_exchange(
             true,
@@ -174,6 +174,10 @@ pub enum RedrawEvent {
         mode: EditorMode,
         mode_index: u64,
     },
+    // not from redraw, sent by autocmd when `iminsert` or `imsearch` changed.
+    LangmapChange {
+        enabled: bool,
+    },
     MouseOn,
     MouseOff,
     BusyStart,
@@ -190,6 +194,8 @@ pub enum RedrawEvent {
     HighlightAttributesDefine {
         id: u64,
         style: Style,
+        // `hi_name` and `ui_name` of hl-state info.
+        groups: Vec<String>,
     },
     HighlightGroupSet {
         id: u64,
@@ -232,15 +238,20 @@ pub enum RedrawEvent {
     },
     WindowFloatPosition {
         grid: u64,
+        #[derivative(Debug = "ignore")]
+        window: nvim::Window<TxWrapper>,
         anchor: WindowAnchor,
         anchor_grid: u64,
         anchor_row: f64,
         anchor_column: f64,
         focusable: bool,
+        // zindex of the float.
         sort_order: Option<u64>,
     },
     WindowExternalPosition {
         grid: u64,
+        #[derivative(Debug = "ignore")]
+        window: nvim::Window<TxWrapper>,
     },
     WindowHide {
         grid: u64,
@@ -307,6 +318,51 @@ pub enum RedrawEvent {
     MessageHistoryShow {
         entries: Vec<(MessageKind, StyledContent)>,
     },
+    // not from redraw, buffer of window on grid being attached for minimap,
+    // lines follow in `BufferLines`.
+    BufferAttach {
+        grid: u64,
+        window: Value,
+        buffer: Value,
+    },
+    // not from redraw, window on grid shows the attached buffer, none if
+    // attaching failed.
+    BufferWatched {
+        grid: u64,
+        window: Option<Value>,
+    },
+    // not from redraw, `nvim_buf_detach_event` of attached buffer.
+    BufferDetach {
+        buffer: Value,
+    },
+    // not from redraw, `BufWinEnter` of any window.
+    BufferWindowEnter,
+    // not from redraw, `nvim_buf_lines_event` of attached buffer.
+    BufferLines {
+        buffer: Value,
+        first_line: u64,
+        // none for the end of buffer.
+        last_line: Option<u64>,
+        lines: Vec<String>,
+    },
+    // not from redraw, highlights of all lines of buffer.
+    BufferHighlights {
+        buffer: Value,
+        highlights: Vec<BufferHighlight>,
+    },
+    // not from redraw, `g:rv_{name}` changed and applied to settings.
+    SettingChanged {
+        name: String,
+    },
+}
+
+/// Highlighted text of buffer, {end_column} none for end of line.
+#[derive(Clone, Debug)]
+pub struct BufferHighlight {
+    pub line: u64,
+    pub start_column: u64,
+    pub end_column: Option<u64>,
+    pub color: Color,
 }
 
 #[derive(Debug)]
@@ -465,6 +521,9 @@ fn parse_mode_info_set(mode_info_set_arguments: Vec<Value>) -> Result<RedrawEven
                 "attr_id" => {
                     mode_info.style = Some(parse_u64(value)?);
                 }
+                "attr_id_lm" => {
+                    mode_info.lstyle = Some(parse_u64(value)?);
+                }
                 _ => {}
             }
         }
@@ -562,6 +621,12 @@ fn parse_style(style_map: Value) -> Result<Style> {
                 }
                 ("underline", Value::Boolean(underline)) => style.underline = underline,
                 ("undercurl", Value::Boolean(undercurl)) => style.undercurl = undercurl,
+                ("underdouble", Value::Boolean(underdouble)) => style.underdouble = underdouble,
+                ("underdotted", Value::Boolean(underdotted)) => style.underdotted = underdotted,
+                ("underdashed", Value::Boolean(underdashed)) => style.underdashed = underdashed,
+                ("altfont", Value::Boolean(altfont)) => style.altfont = altfont,
+                ("nocombine", Value::Boolean(nocombine)) => style.nocombine = nocombine,
+                ("url", Value::String(url)) => style.url = url.into_str(),
                 ("blend", Value::Integer(blend)) => style.blend = blend.as_u64().unwrap() as u8,
                 _ => debug!("Ignored style attribute: {}", name),
             }
@@ -574,12 +639,25 @@ fn parse_style(style_map: Value) -> Result<Style> {
 }
 
 fn parse_hl_attr_define(hl_attr_define_arguments: Vec<Value>) -> Result<RedrawEvent> {
-    let [id, attributes, _terminal_attributes, _info] = extract_values(hl_attr_define_arguments)?;
+    let [id, attributes, _terminal_attributes, info] = extract_values(hl_attr_define_arguments)?;
 
     let style = parse_style(attributes)?;
+    let mut groups = Vec::new();
+    for item in parse_array(info)? {
+        for (key, value) in parse_map(item)? {
+            if let ("hi_name" | "ui_name", Some(name)) =
+                (key.as_str().unwrap_or_default(), value.as_str())
+            {
+                if !groups.iter().any(|group| group == name) {
+                    groups.push(name.to_string());
+                }
+            }
+        }
+    }
     Ok(RedrawEvent::HighlightAttributesDefine {
         id: parse_u64(id)?,
         style,
+        groups,
     })
 }
 
@@ -722,8 +800,11 @@ fn parse_window_anchor(value: Value) -> Result<WindowAnchor> {
     }
 }
 
-fn parse_win_float_pos(win_float_pos_arguments: Vec<Value>) -> Result<RedrawEvent> {
-    let ([grid, _window, anchor, anchor_grid, anchor_row, anchor_column, focusable], [sort_order]) =
+fn parse_win_float_pos(
+    win_float_pos_arguments: Vec<Value>,
+    neovim: nvim::Neovim<TxWrapper>,
+) -> Result<RedrawEvent> {
+    let ([grid, window, anchor, anchor_grid, anchor_row, anchor_column, focusable], [sort_order]) =
         extract_values_with_optional(win_float_pos_arguments)?;
 
     let sort_order = if let Some(sort_order) = sort_order {
@@ -734,6 +815,7 @@ fn parse_win_float_pos(win_float_pos_arguments: Vec<Value>) -> Result<RedrawEven
 
     Ok(RedrawEvent::WindowFloatPosition {
         grid: parse_u64(grid)?,
+        window: nvim::Window::new(window, neovim),
         anchor: parse_window_anchor(anchor)?,
         anchor_grid: parse_u64(anchor_grid)?,
         anchor_row: parse_f64(anchor_row)?,
@@ -743,11 +825,15 @@ fn parse_win_float_pos(win_float_pos_arguments: Vec<Value>) -> Result<RedrawEven
     })
 }
 
-fn parse_win_external_pos(win_external_pos_arguments: Vec<Value>) -> Result<RedrawEvent> {
-    let [grid, _window] = extract_values(win_external_pos_arguments)?;
+fn parse_win_external_pos(
+    win_external_pos_arguments: Vec<Value>,
+    neovim: nvim::Neovim<TxWrapper>,
+) -> Result<RedrawEvent> {
+    let [grid, window] = extract_values(win_external_pos_arguments)?;
 
     Ok(RedrawEvent::WindowExternalPosition {
         grid: parse_u64(grid)?,
+        window: nvim::Window::new(window, neovim),
     })
 }
 
@@ -913,6 +999,28 @@ fn parse_msg_history_show(msg_history_show_arguments: Vec<Value>) -> Result<Redr
     })
 }
 
+/// Parse `nvim_buf_lines_event`.
+pub fn parse_buf_lines_event(arguments: Vec<Value>) -> Result<RedrawEvent> {
+    let [buffer, _changedtick, first_line, last_line, lines, _more] = extract_values(arguments)?;
+
+    Ok(RedrawEvent::BufferLines {
+        buffer,
+        first_line: parse_u64(first_line)?,
+        last_line: u64::try_from(parse_i64(last_line)?).ok(),
+        lines: parse_array(lines)?
+            .into_iter()
+            .map(parse_string)
+            .collect::<Result<_>>()?,
+    })
+}
+
+/// Parse `nvim_buf_detach_event`.
+pub fn parse_buf_detach_event(arguments: Vec<Value>) -> Result<RedrawEvent> {
+    let [buffer] = extract_values(arguments)?;
+
+    Ok(RedrawEvent::BufferDetach { buffer })
+}
+
 pub fn parse_redraw_event(
     event_value: Value,
     neovim: nvim::Neovim<TxWrapper>,
@@ -949,8 +1057,8 @@ pub fn parse_redraw_event(
             "grid_cursor_goto" => Some(parse_grid_cursor_goto(event_parameters)?),
             "grid_scroll" => Some(parse_grid_scroll(event_parameters)?),
             "win_pos" => Some(parse_win_pos(event_parameters, neovim.clone())?),
-            "win_float_pos" => Some(parse_win_float_pos(event_parameters)?),
-            "win_external_pos" => Some(parse_win_external_pos(event_parameters)?),
+            "win_float_pos" => Some(parse_win_float_pos(event_parameters, neovim.clone())?),
+            "win_external_pos" => Some(parse_win_external_pos(event_parameters, neovim.clone())?),
             "win_hide" => Some(parse_win_hide(event_parameters)?),
             "win_close" => Some(parse_win_close(event_parameters)?),
             "msg_set_pos" => Some(parse_msg_set_pos(event_parameters)?),
@@ -6,7 +6,10 @@ use nvim::{Handler, Neovim, Value};
 #[cfg(windows)]
 use crate::bridge::ui_commands::{ParallelCommand, UiCommand};
 use crate::{
-    bridge::{events::parse_redraw_event, TxWrapper},
+    bridge::{
+        events::{parse_buf_detach_event, parse_buf_lines_event, parse_redraw_event, RedrawEvent},
+        TxWrapper,
+    },
     event_aggregator::EVENT_AGGREGATOR,
     running_tracker::*,
     settings::SETTINGS,
@@ -74,7 +77,14 @@ impl Handler for NeovimHandler {
                 }
             }
             "setting_changed" => {
+                let name = arguments
+                    .first()
+                    .and_then(|name| name.as_str())
+                    .map(String::from);
                 SETTINGS.handle_changed_notification(arguments);
+                if let Some(name) = name {
+                    EVENT_AGGREGATOR.send(RedrawEvent::SettingChanged { name });
+                }
             }
             "neovide.quit" => {
                 let error_code = arguments[0]
@@ -90,6 +100,26 @@ impl Handler for NeovimHandler {
             "neovide.unregister_right_click" => {
                 EVENT_AGGREGATOR.send(UiCommand::Parallel(ParallelCommand::UnregisterRightClick));
             }
+            "neovide.langmap" => {
+                let enabled = arguments
+                    .first()
+                    .and_then(|v| v.as_bool().or_else(|| v.as_i64().map(|v| v != 0)))
+                    .unwrap_or(false);
+                EVENT_AGGREGATOR.send(RedrawEvent::LangmapChange { enabled });
+            }
+            "nvim_buf_lines_event" => match parse_buf_lines_event(arguments) {
+                Ok(event) => EVENT_AGGREGATOR.send(event),
+                Err(err) => log::error!("Could not parse buffer lines event: {}", err),
+            },
+            "nvim_buf_detach_event" => match parse_buf_detach_event(arguments) {
+                Ok(event) => EVENT_AGGREGATOR.send(event),
+                Err(err) => log::error!("Could not parse buffer detach event: {}", err),
+            },
+            // undo back to saved text and such, no lines changed.
+            "nvim_buf_changedtick_event" => {}
+            "neovide.buf_win_enter" => {
+                EVENT_AGGREGATOR.send(RedrawEvent::BufferWindowEnter);
+            }
             "neovide.set_clipboard" => {
                 // set_remote_clipboard(arguments).ok();
                 log::error!("set remote clipboard ignored.")
@@ -0,0 +1,244 @@
+//! Buffer shown in the minimap, kept up to date by `nvim_buf_attach`.
+
+use nvim::error::CallError;
+use nvim::{Buffer, Neovim, Value, Window};
+use rustc_hash::FxHashMap;
+
+use crate::bridge::{BufferHighlight, RedrawEvent, TxWrapper};
+use crate::color::{Color, ColorExt};
+use crate::event_aggregator::EVENT_AGGREGATOR;
+
+type Result<T> = std::result::Result<T, Box<CallError>>;
+
+// Treesitter captures of buffer, as
+// [line, column, end line, end column, highlight group].
+const TREESITTER_CAPTURES: &str = r#"
+local buffer = ...
+local highlighter = vim.treesitter.highlighter.active[buffer]
+local captures = {}
+if not highlighter then
+  return captures
+end
+highlighter.tree:for_each_tree(function(tstree, tree)
+  local query = tstree and highlighter:get_query(tree:lang()):query()
+  if not query then
+    return
+  end
+  for id, node in query:iter_captures(tstree:root(), buffer, 0, -1) do
+    local line, column, end_line, end_column = node:range()
+    table.insert(captures, { line, column, end_line, end_column, "@" .. query.captures[id] })
+  end
+end)
+return captures
+"#;
+
+/// Attach to the buffer of `window` on `grid` unless it is `current`, which
+/// gets detached otherwise. Lines of the buffer arrive in the first
+/// `nvim_buf_lines_event`, no edit is missed between reading and attaching.
+pub async fn watch_buffer(
+    nvim: &Neovim<TxWrapper>,
+    grid: u64,
+    window: Value,
+    current: Option<Value>,
+) -> Result<()> {
+    let window = Window::new(window, nvim.clone());
+    let buffer = window.get_buf().await?;
+    if current.as_ref() == Some(buffer.get_value()) {
+        EVENT_AGGREGATOR.send(RedrawEvent::BufferWatched {
+            grid,
+            window: Some(window.get_value().clone()),
+        });
+        return Ok(());
+    }
+    if let Some(current) = current {
+        Buffer::new(current, nvim.clone()).detach().await.ok();
+    }
+    // sent before attaching, lines events follow it.
+    EVENT_AGGREGATOR.send(RedrawEvent::BufferAttach {
+        grid,
+        window: window.get_value().clone(),
+        buffer: buffer.get_value().clone(),
+    });
+    if let Err(err) = buffer.attach(true, vec![]).await {
+        EVENT_AGGREGATOR.send(RedrawEvent::BufferDetach {
+            buffer: buffer.get_value().clone(),
+        });
+        return Err(err);
+    }
+    log::debug!("minimap attached to buffer of grid {}", grid);
+    Ok(())
+}
+
+/// Send highlights of all lines of `buffer`.
+pub async fn send_highlights(nvim: &Neovim<TxWrapper>, buffer: Value) -> Result<()> {
+    let buffer = Buffer::new(buffer, nvim.clone());
+    let highlights = highlights(nvim, &buffer).await?;
+    EVENT_AGGREGATOR.send(RedrawEvent::BufferHighlights {
+        buffer: buffer.get_value().clone(),
+        highlights,
+    });
+    Ok(())
+}
+
+/// Treesitter captures and highlighted extmarks of all namespaces, colored
+/// by foreground of their highlight group, extmarks drawn over captures.
+async fn highlights(
+    nvim: &Neovim<TxWrapper>,
+    buffer: &Buffer<TxWrapper>,
+) -> Result<Vec<BufferHighlight>> {
+    let args = vec![buffer.get_value().clone()];
+    // buffers without treesitter, or nvim without the api, only have extmarks.
+    let captures = match nvim.exec_lua(TREESITTER_CAPTURES, args).await {
+        Ok(Value::Array(captures)) => captures,
+        Ok(_) => Vec::new(),
+        Err(err) => {
+            log::debug!("minimap failed to read treesitter captures: {}", err);
+            Vec::new()
+        }
+    };
+
+    let (start, end) = (Value::from(0), Value::from(-1));
+    let opts = vec![(Value::from("details"), Value::from(true))];
+    let extmarks = buffer.get_extmarks(-1, start, end, opts).await?;
+
+    let mut colors: FxHashMap<String, Option<Color>> = FxHashMap::default();
+    let mut highlights = Vec::new();
+    let marks = captures
+        .into_iter()
+        .filter_map(parse_capture)
+        .chain(extmarks.into_iter().filter_map(parse_extmark));
+    for (line, start_column, end_line, end_column, group) in marks {
+        let color = match colors.get(&group) {
+            Some(color) => *color,
+            None => {
+                let color = nvim
+                    .get_hl_by_name(&group, true)
+                    .await
+                    .ok()
+                    .and_then(|hl| {
+                        hl.into_iter()
+                            .find(|(k, _)| k.as_str() == Some("foreground"))
+                            .and_then(|(_, v)| v.as_u64())
+                    })
+                    .map(Color::from_u64);
+                colors.insert(group, color);
+                color
+            }
+        };
+        if let Some(color) = color {
+            highlights.push(BufferHighlight {
+                line,
+                start_column,
+                end_column: end_column.filter(|_| end_line == Some(line)),
+                color,
+            });
+        }
+    }
+    Ok(highlights)
+}
+
+// line, column, end line, end column and highlight group of treesitter
+// capture.
+fn parse_capture(capture: Value) -> Option<(u64, u64, Option<u64>, Option<u64>, String)> {
+    let mut values = match capture {
+        Value::Array(values) if values.len() == 5 => values.into_iter(),
+        _ => return None,
+    };
+    let line = values.next()?.as_u64()?;
+    let column = values.next()?.as_u64()?;
+    let end_line = values.next()?.as_u64();
+    let end_column = values.next()?.as_u64();
+    let group = values.next()?.as_str()?.to_string();
+    Some((line, column, end_line, end_column, group))
+}
+
+// line, column, end line, end column and highlight group of extmark.
+fn parse_extmark(extmark: Value) -> Option<(u64, u64, Option<u64>, Option<u64>, String)> {
+    let mut values = match extmark {
+        Value::Array(values) if values.len() == 4 => values.into_iter().skip(1),
+        _ => return None,
+    };
+    let line = values.next()?.as_u64()?;
+    let column = values.next()?.as_u64()?;
+    let details = match values.next()? {
+        Value::Map(details) => details,
+        _ => return None,
+    };
+    let mut end_line = None;
+    let mut end_column = None;
+    let mut group = None;
+    for (k, v) in details {
+        match k.as_str() {
+            Some("end_row") => end_line = v.as_u64(),
+            Some("end_col") => end_column = v.as_u64(),
+            Some("hl_group") => group = v.as_str().map(String::from),
+            _ => {}
+        }
+    }
+    Some((line, column, end_line, end_column, group?))
+}
+
+/// Stop updates of `buffer`.
+pub async fn detach_buffer(nvim: &Neovim<TxWrapper>, buffer: Value) -> Result<()> {
+    Buffer::new(buffer, nvim.clone()).detach().await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn extmark(details: Vec<(&str, Value)>) -> Value {
+        let details = details
+            .into_iter()
+            .map(|(k, v)| (Value::from(k), v))
+            .collect();
+        Value::from(vec![
+            Value::from(1),
+            Value::from(3),
+            Value::from(5),
+            Value::Map(details),
+        ])
+    }
+
+    #[test]
+    fn test_parse_extmark() {
+        let mark = extmark(vec![
+            ("end_row", Value::from(3)),
+            ("end_col", Value::from(9)),
+            ("hl_group", Value::from("Comment")),
+            ("priority", Value::from(4096)),
+        ]);
+        assert_eq!(
+            parse_extmark(mark),
+            Some((3, 5, Some(3), Some(9), "Comment".to_string()))
+        );
+
+        let mark = extmark(vec![("hl_group", Value::from("Search"))]);
+        assert_eq!(
+            parse_extmark(mark),
+            Some((3, 5, None, None, "Search".to_string()))
+        );
+
+        // virtual text or signs without highlight.
+        let mark = extmark(vec![("end_row", Value::from(3))]);
+        assert_eq!(parse_extmark(mark), None);
+        assert_eq!(parse_extmark(Value::from(vec![Value::from(1)])), None);
+    }
+
+    #[test]
+    fn test_parse_capture() {
+        let capture = Value::from(vec![
+            Value::from(2),
+            Value::from(0),
+            Value::from(4),
+            Value::from(1),
+            Value::from("@string"),
+        ]);
+        assert_eq!(
+            parse_capture(capture),
+            Some((2, 0, Some(4), Some(1), "@string".to_string()))
+        );
+        assert_eq!(parse_capture(Value::from("@string")), None);
+    }
+}
@@ -3,6 +3,7 @@ mod command;
 pub mod create;
 mod events;
 mod handler;
+mod minimap;
 mod setup;
 mod tx_wrapper;
 mod ui_commands;
@@ -12,7 +13,9 @@ use std::sync::Arc;
 use log::{error, info};
 use nvim::UiAttachOptions;
 
-use crate::{running_tracker::*, settings::*, ConnectionMode, Opts};
+use crate::{
+    event_aggregator::EVENT_AGGREGATOR, running_tracker::*, settings::*, ConnectionMode, Opts,
+};
 
 pub use command::create_nvim_command;
 pub use events::*;
@@ -72,7 +75,10 @@ pub async fn open(opts: Opts) {
     let nvim = Arc::new(nvim);
 
     start_ui_command_handler(nvim.clone());
-    SETTINGS.read_initial_values(&nvim).await;
+    // settings read before changed listeners are set up.
+    for name in SETTINGS.read_initial_values(&nvim).await {
+        EVENT_AGGREGATOR.send(RedrawEvent::SettingChanged { name });
+    }
     SETTINGS.setup_changed_listeners(&nvim).await;
 
     let running_tracker = RUNNING_TRACKER.clone();
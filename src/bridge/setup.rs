@@ -2,13 +2,13 @@ use log::{info, warn};
 use nvim::{Neovim, Value};
 
 use crate::bridge::{events::*, TxWrapper};
+use crate::settings;
 
 pub async fn setup_neovide_remote_clipboard(nvim: &Neovim<TxWrapper>, neovide_channel: u64) {
     // users can opt-out with
-    // vim: `let g:neovide_no_custom_clipboard = v:true`
-    // lua: `vim.g.neovide_no_custom_clipboard = true`
-    let no_custom_clipboard = nvim
-        .get_var("neovide_no_custom_clipboard")
+    // vim: `let g:rv_no_custom_clipboard = v:true`
+    // lua: `vim.g.rv_no_custom_clipboard = true`
+    let no_custom_clipboard = settings::get_var(nvim, "no_custom_clipboard")
         .await
         .ok()
         .and_then(|v| v.as_bool());
@@ -139,6 +139,26 @@ pub async fn setup_neovide_specific_state(nvim: &Neovim<TxWrapper>, is_remote: b
         .await
         .ok();
 
+    // Report whether language mappings are active, for drawing the cursor with `lCursor`.
+    // i_CTRL-^ and c_CTRL-^ toggle them without OptionSet, the next typed char reports it.
+    nvim.command("augroup RvLangmap | augroup END").await.ok();
+    nvim.command("autocmd! RvLangmap").await.ok();
+    nvim.command(concat!(
+        "autocmd RvLangmap ",
+        "OptionSet,BufEnter,InsertEnter,CmdlineEnter,InsertCharPre,CmdlineChanged * ",
+        "call rpcnotify(1, 'neovide.langmap', ",
+        "(mode() =~# '^c' && &imsearch != -1 ? &imsearch : &iminsert) == 1)"
+    ))
+    .await
+    .ok();
+
+    // Report windows switching buffers, to attach the buffer shown in minimap
+    nvim.command("augroup RvMinimap | augroup END").await.ok();
+    nvim.command("autocmd! RvMinimap").await.ok();
+    nvim.command("autocmd RvMinimap BufWinEnter * call rpcnotify(1, 'neovide.buf_win_enter')")
+        .await
+        .ok();
+
     // Create auto command for retrieving exit code from neovim on quit
     nvim.command("autocmd VimLeave * call rpcnotify(1, 'neovide.quit', v:exiting)")
         .await
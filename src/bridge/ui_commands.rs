@@ -9,7 +9,9 @@ use crate::windows_utils::{
     register_rightclick_directory, register_rightclick_file, unregister_rightclick,
 };
 use crate::{
-    bridge::TxWrapper, event_aggregator::EVENT_AGGREGATOR, keys::ToInput,
+    bridge::{minimap, RedrawEvent, TxWrapper},
+    event_aggregator::EVENT_AGGREGATOR,
+    keys::ToInput,
     running_tracker::RUNNING_TRACKER,
 };
 
@@ -217,7 +219,37 @@ pub enum ParallelCommand {
         width: u64,
         height: u64,
     },
+    ResizeGrid {
+        grid: u64,
+        width: u64,
+        height: u64,
+    },
+    // show 0-based {top_line} on top of window, by handle.
+    ScrollWindow {
+        window: nvim::Value,
+        top_line: u64,
+    },
+    // make window current, not typed as keys in any mode.
+    FocusWindow {
+        window: nvim::Value,
+    },
+    // attach minimap to buffer of window on grid, detach {current}.
+    WatchBuffer {
+        grid: u64,
+        window: nvim::Value,
+        current: Option<nvim::Value>,
+    },
+    // stop updates of buffer attached for minimap.
+    DetachBuffer {
+        buffer: nvim::Value,
+    },
+    // highlights of all lines for minimap.
+    BufferHighlights {
+        buffer: nvim::Value,
+    },
     FileDrop(String),
+    // error message shown by nvim.
+    ShowError(String),
     FocusLost,
     FocusGained,
     DisplayAvailableFonts(Vec<String>),
@@ -237,6 +269,51 @@ impl ParallelCommand {
                 .ui_try_resize(width.max(10) as i64, height.max(3) as i64)
                 .await
                 .expect("Resize failed"),
+            ParallelCommand::ResizeGrid {
+                grid,
+                width,
+                height,
+            } => {
+                // grid may be closed by nvim before the request arrives.
+                if let Err(err) = nvim
+                    .ui_try_resize_grid(grid as i64, width.max(1) as i64, height.max(1) as i64)
+                    .await
+                {
+                    log::error!("Resize grid {} failed: {}", grid, err);
+                }
+            }
+            ParallelCommand::ScrollWindow { window, top_line } => {
+                let command = format!("call winrestview({{'topline': {}}})", top_line + 1);
+                nvim.call_function("win_execute", call_args![window, command])
+                    .await
+                    .ok();
+            }
+            ParallelCommand::FocusWindow { window } => {
+                let window = nvim::Window::new(window, nvim.clone());
+                if let Err(err) = nvim.set_current_win(&window).await {
+                    log::warn!("failed to focus window: {}", err);
+                }
+            }
+            ParallelCommand::WatchBuffer {
+                grid,
+                window,
+                current,
+            } => {
+                if let Err(err) = minimap::watch_buffer(nvim, grid, window, current).await {
+                    log::warn!("minimap failed to attach buffer: {}", err);
+                    EVENT_AGGREGATOR.send(RedrawEvent::BufferWatched { grid, window: None });
+                }
+            }
+            ParallelCommand::DetachBuffer { buffer } => {
+                if let Err(err) = minimap::detach_buffer(nvim, buffer).await {
+                    log::warn!("minimap failed to detach buffer: {}", err);
+                }
+            }
+            ParallelCommand::BufferHighlights { buffer } => {
+                if let Err(err) = minimap::send_highlights(nvim, buffer).await {
+                    log::debug!("minimap failed to read highlights: {}", err);
+                }
+            }
             ParallelCommand::FocusLost => nvim
                 .command("if exists('#FocusLost') | doautocmd <nomodeline> FocusLost | endif")
                 .await
@@ -245,6 +322,9 @@ impl ParallelCommand {
                 .command("if exists('#FocusGained') | doautocmd <nomodeline> FocusGained | endif")
                 .await
                 .expect("Focus Gained Failed"),
+            ParallelCommand::ShowError(message) => {
+                nvim.err_writeln(&message).await.ok();
+            }
             ParallelCommand::FileDrop(path) => {
                 nvim.command(format!("e {}", path).as_str()).await.ok();
             }
@@ -56,7 +56,7 @@
                                     let attrs = pango::AttrList::new();
                                     cell.attrs.iter().filter_map(|attr| {
                                         match attr.type_() {
-                                            AttrType::Family | AttrType::Style | AttrType::Weight | AttrType::Variant | AttrType::Underline | AttrType::Strikethrough | AttrType::Overline => {
+                                            AttrType::Family | AttrType::Style | AttrType::Weight | AttrType::Variant | AttrType::Strikethrough | AttrType::Overline => {
                                                 let mut attr = attr.clone();
                                                 attr.set_start_index(0);
                                                 attr.set_end_index(pango::ATTR_INDEX_TO_TEXT_END);
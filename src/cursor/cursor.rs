@@ -3,6 +3,7 @@ use std::rc::Rc;
 
 use parking_lot::RwLock;
 
+use crate::bridge::EditorMode;
 use crate::color::Color;
 use crate::grapheme::Coord;
 use crate::metrics::Metrics;
@@ -30,6 +31,8 @@ impl CursorShape {
 pub struct CursorMode {
     pub shape: Option<CursorShape>,
     pub style: Option<u64>,
+    // `lCursor`, used while langmap is active.
+    pub lstyle: Option<u64>,
     pub cell_percentage: Option<f64>,
     pub blinkwait: Option<u64>,
     pub blinkon: Option<u64>,
@@ -49,9 +52,13 @@ pub struct Cursor {
     pub blinkon: Option<u64>,
     pub blinkoff: Option<u64>,
     pub style: Option<u64>,
+    pub lstyle: Option<u64>,
+    pub langmap: bool,
+    pub focused: bool,
     pub enabled: bool,
     pub width: f64,
     pub cell: TextCell,
+    pub editor_mode: EditorMode,
 
     pub pctx: Rc<pango::Context>,
     pub metrics: Rc<Cell<Metrics>>,
@@ -69,6 +76,9 @@ impl Cursor {
             coord: (0, 0).into(),
             shape: CursorShape::Block,
             style: None,
+            lstyle: None,
+            langmap: false,
+            focused: true,
             cell_percentage: None,
             blinkwait: None,
             blinkon: None,
@@ -76,6 +86,7 @@ impl Cursor {
             enabled: true,
             width: 1.,
             cell: TextCell::default(),
+            editor_mode: EditorMode::Normal,
 
             pctx,
             hldefs,
@@ -90,11 +101,13 @@ impl Cursor {
             self.cell_percentage,
             percentage
         );
+        // block and underline cover both cells of double width character.
+        let cells = self.width.max(1.);
         match self.shape {
             CursorShape::Block => (
                 self.coord.col * width,
                 self.coord.row * height,
-                width,
+                width * cells,
                 height,
             ),
             CursorShape::Vertical => (
@@ -106,44 +119,60 @@ impl Cursor {
             CursorShape::Horizontal => (
                 self.coord.col * width,
                 self.coord.row * height + height - height * percentage,
-                width,
+                width * cells,
                 height * percentage,
             ),
         }
     }
 
+    /// Highlight of `attr_id`, `attr_id_lm` while langmap is active, none
+    /// for the default which draws the cell under cursor reversed.
+    fn style_id(&self) -> Option<u64> {
+        let style = if self.langmap {
+            self.lstyle.or(self.style)
+        } else {
+            self.style
+        };
+        style.filter(|&s| s != HighlightDefinitions::DEFAULT)
+    }
+
+    /// (foreground, background) of the cell under cursor.
+    fn cell_colors(&self, hldefs: &HighlightDefinitions) -> (Color, Color) {
+        let default_colors = hldefs.defaults().unwrap();
+        let style = self.cell.hldef.and_then(|id| hldefs.get(id));
+        let foreground = style
+            .map(|style| style.foreground(default_colors))
+            .unwrap_or_else(|| default_colors.foreground.unwrap());
+        let background = style
+            .and_then(|style| style.background())
+            .unwrap_or_else(|| default_colors.background.unwrap());
+        (foreground, background)
+    }
+
     pub fn foreground(&self) -> Color {
         let hldefs = self.hldefs.read();
-        let default_colors = hldefs.defaults().unwrap();
-        if let Some(style_id) = self.style.filter(|&s| s != HighlightDefinitions::DEFAULT) {
-            let style = hldefs.get(style_id).unwrap();
-            style
-                .colors
-                .foreground
-                .unwrap_or_else(|| default_colors.background.unwrap())
-        } else {
-            default_colors.background.unwrap()
-        }
+        let style = self.style_id().and_then(|id| hldefs.get(id));
+        style
+            .and_then(|style| style.colors.foreground)
+            .unwrap_or_else(|| self.cell_colors(&hldefs).1)
     }
 
     pub fn background(&self) -> Color {
         let hldefs = self.hldefs.read();
-        let default_colors = hldefs.defaults().unwrap();
-        let (mut color, blend) =
-            if let Some(style_id) = self.style.filter(|&s| s != HighlightDefinitions::DEFAULT) {
-                let style = hldefs.get(style_id).unwrap();
-                let color = style
-                    .colors
-                    .background
-                    .unwrap_or_else(|| default_colors.foreground.unwrap());
-                (color, style.blend)
-            } else {
-                let blend = hldefs
-                    .get(HighlightDefinitions::DEFAULT)
-                    .map(|s| s.blend)
-                    .unwrap_or(100);
-                (default_colors.foreground.unwrap(), blend)
-            };
+        let (mut color, blend) = if let Some(style) = self.style_id().and_then(|id| hldefs.get(id))
+        {
+            let color = style
+                .colors
+                .background
+                .unwrap_or_else(|| self.cell_colors(&hldefs).0);
+            (color, style.blend)
+        } else {
+            let blend = hldefs
+                .get(HighlightDefinitions::DEFAULT)
+                .map(|s| s.blend)
+                .unwrap_or(100);
+            (self.cell_colors(&hldefs).0, blend)
+        };
         let alpha = (100 - blend) as f32 / 100.;
         color.set_alpha(alpha);
         color
@@ -183,26 +212,8 @@ impl Cursor {
         self.width = width;
     }
 
-    pub fn set_mode(&mut self, cursor_mode: CursorMode) {
-        let CursorMode {
-            shape,
-            style,
-            cell_percentage,
-            blinkwait,
-            blinkon,
-            blinkoff,
-        } = cursor_mode;
-
-        if let Some(shape) = shape {
-            self.shape = shape.clone();
-        }
-
-        self.style = style;
-
-        self.cell_percentage = cell_percentage;
-        self.blinkwait = blinkwait;
-        self.blinkon = blinkon;
-        self.blinkoff = blinkoff;
+    pub fn set_editor_mode(&mut self, editor_mode: EditorMode) {
+        self.editor_mode = editor_mode;
     }
 
     pub fn set_grid(&mut self, grid: u64) {
@@ -213,11 +224,19 @@ impl Cursor {
         self.coord = coord;
     }
 
-    /*
-    pub fn change_mode(&mut self, cursor_mode: &CursorMode, styles: &HighlightDefinitions) {
+    pub fn set_langmap(&mut self, langmap: bool) {
+        self.langmap = langmap;
+    }
+
+    pub fn set_focused(&mut self, focused: bool) {
+        self.focused = focused;
+    }
+
+    pub fn change_mode(&mut self, cursor_mode: &CursorMode) {
         let CursorMode {
             shape,
             style,
+            lstyle,
             cell_percentage,
             blinkwait,
             blinkon,
@@ -228,25 +247,13 @@ impl Cursor {
             self.shape = shape.clone();
         }
 
-        if let Some(style) = style {
-            if *style == HighlightDefinitions::DEFAULT {
-                let mut style = styles.get(*style).cloned().unwrap();
-                let bg = style.colors.background;
-                let fg = style.colors.foreground;
-                style.colors.foreground = bg;
-                style.colors.background = fg;
-                self.style.replace(style);
-            } else {
-                self.style = styles.get(*style).cloned();
-            }
-        }
-
+        self.style = *style;
+        self.lstyle = *lstyle;
         self.cell_percentage = *cell_percentage;
         self.blinkwait = *blinkwait;
         self.blinkon = *blinkon;
         self.blinkoff = *blinkoff;
     }
-    */
 }
 
 #[cfg(test)]
@@ -255,8 +262,7 @@ mod tests {
 
     use super::*;
     use crate::color::{Color, Colors};
-    // use rustc_hash::FxHashMap;
-    // use std::sync::Arc;
+    use crate::style::Style;
 
     const COLORS: Lazy<Colors> = Lazy::new(|| Colors {
         foreground: Some(Color::new(0.1, 0.1, 0.1, 0.1)),
@@ -292,6 +298,87 @@ mod tests {
         );
     }
 
+    fn new_cursor() -> Cursor {
+        let hldefs = HighlightDefinitions::new();
+        hldefs.set_defaults(*DEFAULT_COLORS);
+        hldefs.set(1, Style::new(*COLORS));
+        hldefs.set(2, Style::new(*NONE_COLORS));
+        Cursor::new(
+            Rc::new(pango::Context::new()),
+            Rc::new(Metrics::new().into()),
+            Rc::new(RwLock::new(hldefs)),
+        )
+    }
+
+    #[test]
+    fn test_change_mode() {
+        let cursor_mode = CursorMode {
+            shape: Some(CursorShape::Horizontal),
+            style: Some(1),
+            lstyle: Some(2),
+            cell_percentage: Some(100.0),
+            blinkwait: Some(1),
+            blinkon: Some(1),
+            blinkoff: Some(1),
+        };
+        let mut cursor = new_cursor();
+
+        cursor.change_mode(&cursor_mode);
+        assert_eq!(cursor.shape, CursorShape::Horizontal);
+        assert_eq!(cursor.style, Some(1));
+        assert_eq!(cursor.lstyle, Some(2));
+        assert_eq!(cursor.cell_percentage, Some(100.0));
+        assert_eq!(cursor.blinkwait, Some(1));
+        assert_eq!(cursor.blinkon, Some(1));
+        assert_eq!(cursor.blinkoff, Some(1));
+
+        cursor.change_mode(&CursorMode::default());
+        assert_eq!(cursor.shape, CursorShape::Horizontal);
+        assert_eq!(cursor.style, None);
+        assert_eq!(cursor.lstyle, None);
+        assert_eq!(cursor.cell_percentage, None);
+        assert_eq!(cursor.blinkwait, None);
+        assert_eq!(cursor.blinkon, None);
+        assert_eq!(cursor.blinkoff, None);
+    }
+
+    #[test]
+    fn test_colors() {
+        let mut cursor = new_cursor();
+        // default cursor reverses the cell under it.
+        assert_eq!(cursor.foreground(), DEFAULT_COLORS.background.unwrap());
+        cursor.set_cell(TextCell {
+            hldef: Some(1),
+            ..TextCell::default()
+        });
+        assert_eq!(cursor.foreground(), COLORS.background.unwrap());
+
+        cursor.change_mode(&CursorMode {
+            style: Some(1),
+            lstyle: Some(2),
+            ..CursorMode::default()
+        });
+        cursor.set_cell(TextCell::default());
+        assert_eq!(cursor.foreground(), COLORS.foreground.unwrap());
+
+        // lCursor without colors falls back to the cell.
+        cursor.set_langmap(true);
+        assert_eq!(cursor.foreground(), DEFAULT_COLORS.background.unwrap());
+    }
+
+    #[test]
+    fn test_rectangle() {
+        let mut cursor = new_cursor();
+        cursor.set_coord((2, 1).into());
+        assert_eq!(cursor.rectangle(10., 20.), (20., 20., 10., 20.));
+        cursor.set_cell(TextCell {
+            text: "你".to_string(),
+            double_width: true,
+            ..TextCell::default()
+        });
+        assert_eq!(cursor.rectangle(10., 20.), (20., 20., 20., 20.));
+    }
+
     /*
     #[test]
     fn test_foreground() {
@@ -336,45 +423,5 @@ mod tests {
             DEFAULT_COLORS.foreground.unwrap()
         );
     }
-
-    #[test]
-    fn test_change_mode() {
-        let cursor_mode = CursorMode {
-            shape: Some(CursorShape::Horizontal),
-            style: Some(1),
-            cell_percentage: Some(100.0),
-            blinkwait: Some(1),
-            blinkon: Some(1),
-            blinkoff: Some(1),
-        };
-        let mut styles = FxHashMap::default();
-        styles.insert(1, Arc::new(Style::new(COLORS)));
-
-        let mut cursor = Cursor::new();
-
-        cursor.change_mode(&cursor_mode, &styles);
-        assert_eq!(cursor.shape, CursorShape::Horizontal);
-        assert_eq!(cursor.style, styles.get(&1).cloned());
-        assert_eq!(cursor.cell_percentage, Some(100.0));
-        assert_eq!(cursor.blinkwait, Some(1));
-        assert_eq!(cursor.blinkon, Some(1));
-        assert_eq!(cursor.blinkoff, Some(1));
-
-        let cursor_mode_with_none = CursorMode {
-            shape: None,
-            style: None,
-            cell_percentage: None,
-            blinkwait: None,
-            blinkon: None,
-            blinkoff: None,
-        };
-        cursor.change_mode(&cursor_mode_with_none, &styles);
-        assert_eq!(cursor.shape, CursorShape::Horizontal);
-        assert_eq!(cursor.style, styles.get(&1).cloned());
-        assert_eq!(cursor.cell_percentage, None);
-        assert_eq!(cursor.blinkwait, None);
-        assert_eq!(cursor.blinkon, None);
-        assert_eq!(cursor.blinkoff, None);
-    }
     */
 }
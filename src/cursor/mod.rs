@@ -1,15 +1,22 @@
 mod cursor;
-// mod state;
-// mod vfx;
+mod state;
+mod vfx;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
 
 pub use cursor::{Cursor as VimCursor, CursorMode, CursorShape};
-use gtk::prelude::{StyleContextExt, WidgetExt};
+use gtk::prelude::{DrawingAreaExtManual, ObjectExt, WidgetExt};
 
-use relm4::drawing::DrawContext;
 use relm4::{MicroModel, MicroWidgets, Sender};
 
+use state::CursorRenderer;
+pub use state::CursorSettings;
+
 use crate::grapheme::Coord;
-use crate::vimview::TextCell;
+use crate::scheduler::scheduler;
+use crate::vimview::{self, TextCell};
 
 impl MicroModel for VimCursor {
     type Msg = CursorMessage;
@@ -57,11 +64,20 @@ pub enum CursorMessage {
 //     }
 // }
 
+#[derive(Debug)]
+struct CursorState {
+    cursor: Option<VimCursor>,
+    renderer: CursorRenderer,
+    // hidden by blinking.
+    blink_off: bool,
+}
+
 #[derive(Debug)]
 pub struct CursorWidgets {
     da: gtk::DrawingArea,
-    dh: relm4::drawing::DrawHandler,
-    css_provider: gtk::CssProvider,
+    state: Rc<RefCell<CursorState>>,
+    // what restarts blinking when changed.
+    blinking: Option<(u64, Coord, Option<u64>, Option<u64>, Option<u64>)>,
 }
 
 impl MicroWidgets<VimCursor> for CursorWidgets {
@@ -76,39 +92,37 @@ impl MicroWidgets<VimCursor> for CursorWidgets {
         da.set_can_focus(false);
         da.set_sensitive(false);
         da.set_focus_on_click(false);
-        da.set_css_classes(&["blink"]);
 
-        let css_provider = gtk::CssProvider::new();
-        let mut dh = relm4::drawing::DrawHandler::new().unwrap();
-        dh.init(&da);
-        da.style_context()
-            .add_provider(&css_provider, gtk::STYLE_PROVIDER_PRIORITY_APPLICATION);
+        let state = Rc::new(RefCell::new(CursorState {
+            cursor: None,
+            renderer: CursorRenderer::new(),
+            blink_off: false,
+        }));
+        da.set_draw_func(glib::clone!(@strong state => move |_da, cr, _, _| {
+            let state = state.borrow();
+            if let Some(ref cursor) = state.cursor {
+                if !state.blink_off {
+                    cr.save().unwrap();
+                    cursor.drawing(cr, &state.renderer);
+                    cr.restore().unwrap();
+                }
+                state.renderer.draw_vfx(cr, cursor);
+            }
+        }));
 
         CursorWidgets {
             da,
-            dh,
-            css_provider,
+            state,
+            blinking: None,
         }
     }
 
     fn view(&mut self, vc: &VimCursor, _: Sender<CursorMessage>) {
         log::trace!("start cursor view.");
         let instant = std::time::Instant::now();
-        self.da.set_opacity(1.);
-        self.da.remove_css_class("blink");
-        self.da.style_context().remove_provider(&self.css_provider);
-        let cr = self.dh.get_context().unwrap();
-        vc.drawing(&cr);
-        self.da
-            .style_context()
-            .add_provider(&self.css_provider, gtk::STYLE_PROVIDER_PRIORITY_APPLICATION);
-        if let Some(blinking) = vc.maybe_blinking() {
-            self.css_provider.load_from_data(blinking.as_bytes());
-            self.da
-                .style_context()
-                .add_provider(&self.css_provider, gtk::STYLE_PROVIDER_PRIORITY_APPLICATION);
-            self.da.add_css_class("blink");
-        }
+        self.state.borrow_mut().cursor.replace(vc.clone());
+        self.animate();
+        self.blink(vc);
         log::trace!(
             "cursor view used {:.3}ms",
             instant.elapsed().as_secs_f32() * 1000.
@@ -120,48 +134,105 @@ impl MicroWidgets<VimCursor> for CursorWidgets {
     }
 }
 
-impl VimCursor {
-    fn maybe_blinking(&self) -> Option<String> {
-        let blinkon = self.blinkon().filter(|blinkon| *blinkon > 0)?;
-        let blinkoff = self.blinkoff().filter(|blinkoff| *blinkoff > 0)?;
-        let blinkwait = self.blinkwait().filter(|blinkwait| *blinkwait > 0)?;
-        let css = format!(
-            ".blink {{
-  animation-name: blinking;
-  animation-delay: {}ms;
-  animation-duration: {}ms;
-  animation-iteration-count: infinite;
-  animation-timing-function: steps(2, start);
-}}
-
-@keyframes blinking {{
-  {}% {{ opacity: 0; }}
-}}
-",
-            blinkwait,
-            blinkon + blinkoff,
-            blinkon * 100 / (blinkon + blinkoff)
+impl CursorWidgets {
+    /// Drive corners by frame clock until they reach the cursor cell.
+    fn animate(&self) {
+        self.da.queue_draw();
+        let state = self.state.clone();
+        let da = self.da.downgrade();
+        scheduler().animate("cursor", move |dt| {
+            let da = match da.upgrade() {
+                Some(da) => da,
+                None => return false,
+            };
+            let mut state = state.borrow_mut();
+            let CursorState {
+                ref cursor,
+                ref mut renderer,
+                ..
+            } = *state;
+            let animating = cursor
+                .as_ref()
+                .map(|cursor| renderer.animate(cursor, dt))
+                .unwrap_or(false);
+            da.queue_draw();
+            animating
+        });
+    }
+
+    /// Blink as `blinkwait`, `blinkon` and `blinkoff`, restarting when cursor
+    /// moved or mode changed, steady while unfocused.
+    fn blink(&mut self, vc: &VimCursor) {
+        let blinking = (
+            vc.grid,
+            vc.coord.clone(),
+            vc.blinkwait(),
+            vc.blinkon(),
+            vc.blinkoff(),
+        );
+        let times = (
+            vc.blinkwait().filter(|ms| *ms > 0),
+            vc.blinkon().filter(|ms| *ms > 0),
+            vc.blinkoff().filter(|ms| *ms > 0),
+        );
+        let (blinkwait, blinkon, blinkoff) = match times {
+            (Some(wait), Some(on), Some(off)) if vc.focused => (wait, on, off),
+            _ => {
+                self.blinking = None;
+                self.state.borrow_mut().blink_off = false;
+                scheduler().cancel("cursor-blink");
+                return;
+            }
+        };
+        if self.blinking.as_ref() == Some(&blinking) {
+            return;
+        }
+        self.blinking.replace(blinking);
+        self.state.borrow_mut().blink_off = false;
+        let state = self.state.clone();
+        let da = self.da.downgrade();
+        scheduler().timeout(
+            "cursor-blink",
+            Duration::from_millis(blinkwait),
+            move || {
+                let da = da.upgrade()?;
+                let mut state = state.borrow_mut();
+                state.blink_off = !state.blink_off;
+                da.queue_draw();
+                let next = if state.blink_off { blinkoff } else { blinkon };
+                Some(Duration::from_millis(next))
+            },
         );
-        log::debug!("css {} {}: \n{}", blinkon, blinkoff, &css);
-        Some(css)
     }
+}
 
-    fn drawing(&self, cr: &DrawContext) {
-        // clear previous position.
-        cr.set_operator(cairo::Operator::Clear);
-        cr.set_source_rgba(0.0, 0.0, 0.0, 0.0);
-        cr.paint().expect("Couldn't fill context");
-        // paintable.
-        cr.set_operator(cairo::Operator::Over);
+impl VimCursor {
+    fn drawing(&self, cr: &cairo::Context, renderer: &CursorRenderer) {
         let bg = self.background();
         let fg = self.foreground();
         let cell = self.cell();
         let metrics = self.metrics.get();
-        let (x, y, width, height) = self.rectangle(metrics.width(), metrics.height());
+        let (x, y, _, _) = self.rectangle(metrics.width(), metrics.height());
         log::debug!("drawing cursor at {}x{}.", x, y);
+        renderer.path(cr);
+        cr.set_source_rgba(
+            bg.red() as f64,
+            bg.green() as f64,
+            bg.blue() as f64,
+            bg.alpha() as f64,
+        );
+        if !self.focused && self.shape == CursorShape::Block {
+            // hollow box while window unfocused, character stays visible.
+            cr.set_line_width(1.);
+            cr.stroke().unwrap();
+            return;
+        }
         match self.shape {
             CursorShape::Block => {
                 use pango::AttrType;
+                cr.fill_preserve().unwrap();
+                // character stays at destination, visible through moving cursor.
+                cr.clip();
                 let attrs = pango::AttrList::new();
                 cell.attrs
                     .iter()
@@ -170,7 +241,6 @@ impl VimCursor {
                         | AttrType::Style
                         | AttrType::Weight
                         | AttrType::Variant
-                        | AttrType::Underline
                         | AttrType::Strikethrough
                         | AttrType::Overline => {
                             let mut attr = attr.clone();
@@ -202,17 +272,6 @@ impl VimCursor {
                     geometry.set_x_offset(x_offset);
                     log::debug!("cursor glyph width {}", width);
                 }
-                // 试试汉字
-                cr.save().unwrap();
-                cr.rectangle(x, y, width as f64, metrics.height());
-                cr.set_source_rgba(
-                    bg.red() as f64,
-                    bg.green() as f64,
-                    bg.blue() as f64,
-                    bg.alpha() as f64,
-                );
-                cr.fill().unwrap();
-                cr.restore().unwrap();
                 cr.set_source_rgba(
                     fg.red() as f64,
                     fg.green() as f64,
@@ -221,16 +280,26 @@ impl VimCursor {
                 );
                 cr.move_to(x + geometry.width() as f64 / 2., y + metrics.ascent());
                 pangocairo::show_glyph_string(cr, &itemized.analysis().font(), &mut glyph_string);
+                // underline of the cell is drawn by grids, not in attrs, redraw it
+                // in color of the text under cursor.
+                let underline = cell.hldef.and_then(|id| {
+                    self.hldefs
+                        .read()
+                        .get(id)
+                        .and_then(|style| style.underline())
+                });
+                if let Some(underline) = underline {
+                    vimview::draw_underline(
+                        cr,
+                        underline,
+                        x,
+                        x + width as f64,
+                        y + metrics.ascent(),
+                        &metrics,
+                    );
+                }
             }
             _ => {
-                log::debug!("drawing cursor with {}x{}", width, height);
-                cr.set_source_rgba(
-                    bg.red() as f64,
-                    bg.green() as f64,
-                    bg.blue() as f64,
-                    bg.alpha() as f64,
-                );
-                cr.rectangle(x, y, width, height);
                 cr.fill().unwrap();
             }
         }
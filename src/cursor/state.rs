@@ -1,75 +1,55 @@
-use rustc_hash::FxHashMap;
-
-use super::vfx;
 use crate::{
+    animation::*,
     bridge::EditorMode,
-    editor::{Cursor, CursorShape},
-    redraw_scheduler::REDRAW_SCHEDULER,
-    renderer::animation_utils::*,
-    renderer::{GridRenderer, RenderedWindow},
-    settings::{ParseFromValue, SETTINGS},
+    grapheme::Pos,
+    settings::{setting_group, SETTINGS},
 };
 
-const DEFAULT_CELL_PERCENTAGE: f32 = 1.0 / 8.0;
-
-const STANDARD_CORNERS: &[(f32, f32); 4] = &[(-0.5, -0.5), (0.5, -0.5), (0.5, 0.5), (-0.5, 0.5)];
-
-#[derive(SettingGroup)]
-#[setting_prefix = "cursor"]
-#[derive(Clone)]
-pub struct CursorSettings {
-    antialiasing: bool,
-    animation_length: f32,
-    distance_length_adjust: bool,
-    animate_in_insert_mode: bool,
-    animate_command_line: bool,
-    trail_size: f32,
-    vfx_mode: cursor_vfx::VfxMode,
-    vfx_opacity: f32,
-    vfx_particle_lifetime: f32,
-    vfx_particle_density: f32,
-    vfx_particle_speed: f32,
-    vfx_particle_phase: f32,
-    vfx_particle_curl: f32,
-}
-
-impl Default for CursorSettings {
-    fn default() -> Self {
-        CursorSettings {
-            antialiasing: true,
-            animation_length: 0.06,
-            distance_length_adjust: true,
-            animate_in_insert_mode: true,
-            animate_command_line: true,
-            trail_size: 0.7,
-            vfx_mode: cursor_vfx::VfxMode::Disabled,
-            vfx_opacity: 200.0,
-            vfx_particle_lifetime: 1.2,
-            vfx_particle_density: 7.0,
-            vfx_particle_speed: 10.0,
-            vfx_particle_phase: 1.5,
-            vfx_particle_curl: 1.0,
-        }
+use super::cursor::{Cursor, CursorShape};
+use super::vfx::{self, CursorVfx, VfxMode};
+
+const DEFAULT_CELL_PERCENTAGE: f64 = 1.0 / 8.0;
+
+const STANDARD_CORNERS: &[(f64, f64); 4] = &[(-0.5, -0.5), (0.5, -0.5), (0.5, 0.5), (-0.5, 0.5)];
+
+setting_group! {
+    pub struct CursorSettings("cursor") {
+        antialiasing: bool = true,
+        // seconds
+        animation_length: f64 = 0.06,
+        distance_length_adjust: bool = true,
+        animate_in_insert_mode: bool = true,
+        animate_command_line: bool = true,
+        trail_size: f64 = 0.7,
+        vfx_mode: VfxMode = VfxMode::Disabled,
+        // 0 to 255
+        vfx_opacity: f64 = 200.0,
+        // seconds
+        vfx_particle_lifetime: f64 = 1.2,
+        vfx_particle_density: f64 = 7.0,
+        vfx_particle_speed: f64 = 10.0,
+        vfx_particle_phase: f64 = 1.5,
+        vfx_particle_curl: f64 = 1.0,
     }
 }
 
 #[derive(Debug, Clone)]
 pub struct Corner {
-    start_position: Point,
-    current_position: Point,
-    relative_position: Point,
-    previous_destination: Point,
-    length_multiplier: f32,
-    t: f32,
+    start_position: Pos,
+    current_position: Pos,
+    relative_position: Pos,
+    previous_destination: Pos,
+    length_multiplier: f64,
+    t: f64,
 }
 
 impl Corner {
     pub fn new() -> Corner {
         Corner {
-            start_position: Point::new(0.0, 0.0),
-            current_position: Point::new(0.0, 0.0),
-            relative_position: Point::new(0.0, 0.0),
-            previous_destination: Point::new(-1000.0, -1000.0),
+            start_position: Pos::new(0.0, 0.0),
+            current_position: Pos::new(0.0, 0.0),
+            relative_position: Pos::new(0.0, 0.0),
+            previous_destination: Pos::new(-1000.0, -1000.0),
             length_multiplier: 1.0,
             t: 0.0,
         }
@@ -78,9 +58,9 @@ impl Corner {
     pub fn update(
         &mut self,
         settings: &CursorSettings,
-        font_dimensions: Point,
-        destination: Point,
-        dt: f32,
+        font_dimensions: Pos,
+        destination: Pos,
+        dt: f64,
         immediate_movement: bool,
     ) -> bool {
         if destination != self.previous_destination {
@@ -98,20 +78,19 @@ impl Corner {
         }
 
         // Check first if animation's over
-        if (self.t - 1.0).abs() < std::f32::EPSILON {
+        if (self.t - 1.0).abs() < std::f64::EPSILON {
             return false;
         }
 
         // Calculate window-space destination for corner
-        let relative_scaled_position: Point = (
+        let relative_scaled_position = Pos::new(
             self.relative_position.x * font_dimensions.x,
             self.relative_position.y * font_dimensions.y,
-        )
-            .into();
+        );
 
         let corner_destination = destination + relative_scaled_position;
 
-        if immediate_movement {
+        if immediate_movement || settings.animation_length <= 0. {
             self.t = 1.0;
             self.current_position = corner_destination;
             return true;
@@ -134,19 +113,19 @@ impl Corner {
 
         let direction_alignment = travel_direction.dot(corner_direction);
 
-        if (self.t - 1.0).abs() < std::f32::EPSILON {
-            // We are at destination, move t out of 0-1 range to stop the animation
-            self.t = 2.0;
+        let corner_dt = dt
+            * lerp(
+                1.0,
+                (1.0 - settings.trail_size).max(0.0).min(1.0),
+                -direction_alignment,
+            );
+        // a jump shorter than one cell has zero multiplier, finish it at once.
+        let length = settings.animation_length * self.length_multiplier;
+        self.t = if length > 0. {
+            (self.t + corner_dt / length).min(1.0)
         } else {
-            let corner_dt = dt
-                * lerp(
-                    1.0,
-                    (1.0 - settings.trail_size).max(0.0).min(1.0),
-                    -direction_alignment,
-                );
-            self.t =
-                (self.t + corner_dt / (settings.animation_length * self.length_multiplier)).min(1.0)
-        }
+            1.0
+        };
 
         self.current_position = ease_point(
             ease_out_expo,
@@ -159,37 +138,32 @@ impl Corner {
     }
 }
 
+/// Four corners chasing the cursor cell, in pixels of the cursor drawing area.
+#[derive(Debug)]
 pub struct CursorRenderer {
     pub corners: Vec<Corner>,
-    cursor: Cursor,
-    destination: Point,
     previous_cursor_shape: Option<CursorShape>,
     previous_editor_mode: EditorMode,
-    cursor_vfx: Option<Box<dyn vfx::CursorVfx>>,
-    previous_vfx_mode: vfx::VfxMode,
+    previous_destination: Pos,
+    previous_vfx_mode: VfxMode,
+    cursor_vfx: Option<Box<dyn CursorVfx>>,
 }
 
 impl CursorRenderer {
     pub fn new() -> CursorRenderer {
         let mut renderer = CursorRenderer {
             corners: vec![Corner::new(); 4],
-            cursor: Cursor::new(),
-            destination: (0.0, 0.0).into(),
-            blink_status: BlinkStatus::new(),
             previous_cursor_shape: None,
             previous_editor_mode: EditorMode::Normal,
+            previous_destination: Pos::new(-1000.0, -1000.0),
+            previous_vfx_mode: VfxMode::Disabled,
             cursor_vfx: None,
-            previous_vfx_mode: cursor_vfx::VfxMode::Disabled,
         };
         renderer.set_cursor_shape(&CursorShape::Block, DEFAULT_CELL_PERCENTAGE);
         renderer
     }
 
-    pub fn update_cursor(&mut self, new_cursor: Cursor) {
-        self.cursor = new_cursor;
-    }
-
-    fn set_cursor_shape(&mut self, cursor_shape: &CursorShape, cell_percentage: f32) {
+    fn set_cursor_shape(&mut self, cursor_shape: &CursorShape, cell_percentage: f64) {
         self.corners = self
             .corners
             .clone()
@@ -219,174 +193,93 @@ impl CursorRenderer {
             .collect::<Vec<Corner>>();
     }
 
-    pub fn update_cursor_destination(
-        &mut self,
-        (font_width, font_height): (u64, u64),
-        windows: &FxHashMap<u64, RenderedWindow>,
-    ) {
-        let (cursor_grid_x, cursor_grid_y) = self.cursor.grid_position;
-
-        if let Some(window) = windows.get(&self.cursor.parent_window_id) {
-            let grid_x = cursor_grid_x as f32 + window.grid_current_position.x;
-            let mut grid_y = cursor_grid_y as f32 + window.grid_current_position.y
-                - (window.current_scroll - window.current_surface.top_line as f32);
-
-            // Prevent the cursor from targeting a position outside its current window. Since only
-            // the vertical direction is effected by scrolling, we only have to clamp the vertical
-            // grid position.
-            grid_y = grid_y
-                .max(window.grid_current_position.y)
-                .min(window.grid_current_position.y + window.grid_size.height as f32 - 1.0);
-
-            self.destination = (grid_x * font_width as f32, grid_y * font_height as f32).into();
-        } else {
-            self.destination = (
-                (cursor_grid_x * font_width) as f32,
-                (cursor_grid_y * font_height) as f32,
-            )
-                .into();
-        }
-    }
-
-    pub fn draw(
-        &mut self,
-        grid_renderer: &mut GridRenderer,
-        current_mode: &EditorMode,
-        canvas: &cairo::Context,
-        dt: f32,
-    ) {
-        let render = self.blink_status.update_status(&self.cursor);
+    /// Move corners one frame toward `cursor`, returns whether still animating.
+    pub fn animate(&mut self, cursor: &Cursor, dt: f64) -> bool {
         let settings = SETTINGS.get::<CursorSettings>();
+        let metrics = cursor.metrics.get();
 
-        if settings.vfx_mode != self.previous_vfx_mode {
-            self.cursor_vfx = cursor_vfx::new_cursor_vfx(&settings.vfx_mode);
-            self.previous_vfx_mode = settings.vfx_mode.clone();
+        let mut cursor_width = metrics.width();
+        if cursor.shape != CursorShape::Vertical {
+            cursor_width *= cursor.width.max(1.);
         }
+        let cursor_dimensions = Pos::new(cursor_width, metrics.height());
+        let destination = Pos::new(
+            cursor.coord.col * metrics.width(),
+            cursor.coord.row * metrics.height(),
+        );
+        let center_destination = destination + cursor_dimensions * 0.5;
 
-        let mut paint = Paint::new(skia_safe::colors::WHITE, None);
-        paint.set_anti_alias(settings.antialiasing);
-
-        let character = self.cursor.grid_cell.0.clone();
-
-        let mut cursor_width = grid_renderer.font_dimensions.width;
-        if self.cursor.double_width && self.cursor.shape == CursorShape::Block {
-            cursor_width *= 2;
+        if self.previous_vfx_mode != settings.vfx_mode {
+            self.cursor_vfx = vfx::new_cursor_vfx(&settings.vfx_mode);
+            self.previous_vfx_mode = settings.vfx_mode.clone();
         }
 
-        let cursor_dimensions: Point = (
-            cursor_width as f32,
-            grid_renderer.font_dimensions.height as f32,
-        )
-            .into();
-
-        let in_insert_mode = matches!(current_mode, EditorMode::Insert);
-
-        let changed_to_from_cmdline = !matches!(self.previous_editor_mode, EditorMode::CmdLine)
-            ^ matches!(current_mode, EditorMode::CmdLine);
-
-        let center_destination = self.destination + cursor_dimensions * 0.5;
-        let new_cursor = Some(self.cursor.shape.clone());
-
-        if self.previous_cursor_shape != new_cursor {
-            self.previous_cursor_shape = new_cursor.clone();
+        let new_cursor = Some(cursor.shape.clone());
+        let shape_changed = self.previous_cursor_shape != new_cursor;
+        if shape_changed {
+            self.previous_cursor_shape = new_cursor;
             self.set_cursor_shape(
-                &new_cursor.unwrap(),
-                self.cursor
-                    .cell_percentage
-                    .unwrap_or(DEFAULT_CELL_PERCENTAGE),
+                &cursor.shape,
+                cursor.cell_percentage.unwrap_or(DEFAULT_CELL_PERCENTAGE),
             );
-
+        }
+        if shape_changed || self.previous_destination != center_destination {
+            self.previous_destination = center_destination;
             if let Some(vfx) = self.cursor_vfx.as_mut() {
                 vfx.restart(center_destination);
             }
         }
 
-        let mut animating = false;
+        let current_mode = &cursor.editor_mode;
+        let in_insert_mode = matches!(current_mode, EditorMode::Insert);
+        let changed_to_from_cmdline = matches!(self.previous_editor_mode, EditorMode::CmdLine)
+            != matches!(current_mode, EditorMode::CmdLine);
+        let immediate_movement = !settings.animate_in_insert_mode && in_insert_mode
+            || !settings.animate_command_line && changed_to_from_cmdline;
 
+        let mut animating = false;
         if !center_destination.is_zero() {
             for corner in self.corners.iter_mut() {
-                let immediate_movement = !settings.animate_in_insert_mode && in_insert_mode
-                    || !settings.animate_command_line && !changed_to_from_cmdline;
-
-                let corner_animating = corner.update(
+                animating |= corner.update(
                     &settings,
                     cursor_dimensions,
                     center_destination,
                     dt,
                     immediate_movement,
                 );
-
-                animating |= corner_animating;
             }
-
-            let vfx_animating = if let Some(vfx) = self.cursor_vfx.as_mut() {
-                vfx.update(&settings, center_destination, cursor_dimensions, dt)
-            } else {
-                false
-            };
-
-            animating |= vfx_animating;
+            if let Some(vfx) = self.cursor_vfx.as_mut() {
+                animating |= vfx.update(&settings, center_destination, cursor_dimensions, dt);
+            }
         }
 
-        if animating {
-            REDRAW_SCHEDULER.queue_next_frame();
-        } else {
+        if !animating {
             self.previous_editor_mode = current_mode.clone();
         }
-        if !(self.cursor.enabled && render) {
-            return;
-        }
-        // Draw Background
-        let background_color = self
-            .cursor
-            .background(&grid_renderer.default_style.colors)
-            .to_color()
-            .with_a(self.cursor.alpha());
-        paint.set_color(background_color);
-
-        // The cursor is made up of four points, so I create a path with each of the four
-        // corners.
-        let mut path = Path::new();
-
-        path.move_to(self.corners[0].current_position);
-        path.line_to(self.corners[1].current_position);
-        path.line_to(self.corners[2].current_position);
-        path.line_to(self.corners[3].current_position);
-        path.close();
-
-        canvas.draw_path(&path, &paint);
-
-        // Draw foreground
-        let foreground_color = self
-            .cursor
-            .foreground(&grid_renderer.default_style.colors)
-            .to_color()
-            .with_a(self.cursor.alpha());
-        paint.set_color(foreground_color);
-
-        canvas.save();
-        canvas.clip_path(&path, None, Some(false));
-
-        let y_adjustment = grid_renderer.shaper.y_adjustment();
-        let style = &self.cursor.grid_cell.1;
-
-        let bold = style.as_ref().map(|x| x.bold).unwrap_or(false);
-        let italic = style.as_ref().map(|x| x.italic).unwrap_or(false);
-
-        let blobs = &grid_renderer.shaper.shape_cached(character, bold, italic);
+        animating
+    }
 
-        for blob in blobs.iter() {
-            canvas.draw_text_blob(
-                &blob,
-                (self.destination.x, self.destination.y + y_adjustment as f32),
-                &paint,
-            );
+    /// Append the cursor outline to current path of `cr`.
+    pub fn path(&self, cr: &cairo::Context) {
+        let settings = SETTINGS.get::<CursorSettings>();
+        cr.set_antialias(if settings.antialiasing {
+            cairo::Antialias::Default
+        } else {
+            cairo::Antialias::None
+        });
+        let first = self.corners[0].current_position;
+        cr.move_to(first.x, first.y);
+        for corner in self.corners[1..].iter() {
+            cr.line_to(corner.current_position.x, corner.current_position.y);
         }
+        cr.close_path();
+    }
 
-        canvas.restore();
-
+    /// Draw particles or highlight of current `vfx_mode` around the cursor.
+    pub fn draw_vfx(&self, cr: &cairo::Context, cursor: &Cursor) {
         if let Some(vfx) = self.cursor_vfx.as_ref() {
-            vfx.render(&settings, canvas, grid_renderer, &self.cursor);
+            let settings = SETTINGS.get::<CursorSettings>();
+            vfx.render(&settings, cr, cursor);
         }
     }
 }
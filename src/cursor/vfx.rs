@@ -1,46 +1,36 @@
-use grapheme::Point;
 use nvim::Value;
 
 use super::cursor::Cursor;
-use crate::{
-    renderer::cursor_renderer::CursorSettings,
-    renderer::{animation_utils::*, grid_renderer::GridRenderer},
-    settings::*,
-};
+use super::state::CursorSettings;
+use crate::{animation::*, grapheme::Pos, settings::ParseFromValue};
 
-pub trait CursorVfx {
+pub trait CursorVfx: std::fmt::Debug {
     fn update(
         &mut self,
         settings: &CursorSettings,
-        current_cursor_destination: Point,
-        cursor_dimensions: Point,
-        dt: f32,
+        current_cursor_destination: Pos,
+        cursor_dimensions: Pos,
+        dt: f64,
     ) -> bool;
-    fn restart(&mut self, position: Point);
-    fn render(
-        &self,
-        settings: &CursorSettings,
-        canvas: &mut Canvas,
-        grid_renderer: &mut GridRenderer,
-        cursor: &Cursor,
-    );
+    fn restart(&mut self, position: Pos);
+    fn render(&self, settings: &CursorSettings, cr: &cairo::Context, cursor: &Cursor);
 }
 
-#[derive(Clone, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum HighlightMode {
     SonicBoom,
     Ripple,
     Wireframe,
 }
 
-#[derive(Clone, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum TrailMode {
     Railgun,
     Torpedo,
     PixieDust,
 }
 
-#[derive(Clone, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum VfxMode {
     Highlight(HighlightMode),
     Trail(TrailMode),
@@ -91,9 +81,10 @@ pub fn new_cursor_vfx(mode: &VfxMode) -> Option<Box<dyn CursorVfx>> {
     }
 }
 
+#[derive(Debug)]
 pub struct PointHighlight {
-    t: f32,
-    center_position: Point,
+    t: f64,
+    center_position: Pos,
     mode: HighlightMode,
 }
 
@@ -101,7 +92,7 @@ impl PointHighlight {
     pub fn new(mode: &HighlightMode) -> PointHighlight {
         PointHighlight {
             t: 0.0,
-            center_position: Point::new(0.0, 0.0),
+            center_position: Pos::new(0.0, 0.0),
             mode: mode.clone(),
         }
     }
@@ -111,80 +102,76 @@ impl CursorVfx for PointHighlight {
     fn update(
         &mut self,
         _settings: &CursorSettings,
-        _current_cursor_destination: Point,
-        _cursor_dimensions: Point,
-        dt: f32,
+        _current_cursor_destination: Pos,
+        _cursor_dimensions: Pos,
+        dt: f64,
     ) -> bool {
         self.t = (self.t + dt * 5.0).min(1.0); // TODO - speed config
         self.t < 1.0
     }
 
-    fn restart(&mut self, position: Point) {
+    fn restart(&mut self, position: Pos) {
         self.t = 0.0;
         self.center_position = position;
     }
 
-    fn render(
-        &self,
-        settings: &CursorSettings,
-        canvas: &mut Canvas,
-        grid_renderer: &mut GridRenderer,
-        cursor: &Cursor,
-    ) {
-        if (self.t - 1.0).abs() < std::f32::EPSILON {
+    fn render(&self, settings: &CursorSettings, cr: &cairo::Context, cursor: &Cursor) {
+        if (self.t - 1.0).abs() < std::f64::EPSILON {
             return;
         }
 
-        let mut paint = Paint::new(skia_safe::colors::WHITE, None);
-        paint.set_blend_mode(BlendMode::SrcOver);
-
-        let colors = &grid_renderer.default_style.colors;
-        let base_color: Color = cursor.background(colors).to_color();
-        let alpha = ease(ease_in_quad, settings.vfx_opacity, 0.0, self.t) as u8;
-        let color = Color::from_argb(alpha, base_color.r(), base_color.g(), base_color.b());
-
-        paint.set_color(color);
+        let color = cursor.background();
+        let alpha = ease(ease_in_quad, settings.vfx_opacity, 0.0, self.t) / 255.;
+        cr.set_source_rgba(
+            color.red() as f64,
+            color.green() as f64,
+            color.blue() as f64,
+            alpha.max(0.).min(1.),
+        );
 
-        let cursor_height = grid_renderer.font_dimensions.height;
-        let size = 3 * cursor_height;
-        let radius = self.t * size as f32;
+        let cursor_height = cursor.metrics.get().height();
+        let size = 3. * cursor_height;
+        let radius = self.t * size;
         let hr = radius * 0.5;
-        let rect = Rect::from_xywh(
-            self.center_position.x - hr,
-            self.center_position.y - hr,
-            radius,
-            radius,
-        );
 
+        cr.save().unwrap();
         match self.mode {
             HighlightMode::SonicBoom => {
-                canvas.draw_oval(&rect, &paint);
+                oval(cr, self.center_position, hr);
+                cr.fill().unwrap();
             }
             HighlightMode::Ripple => {
-                paint.set_style(Style::Stroke);
-                paint.set_stroke_width(cursor_height as f32 * 0.2);
-                canvas.draw_oval(&rect, &paint);
+                oval(cr, self.center_position, hr);
+                cr.set_line_width(cursor_height * 0.2);
+                cr.stroke().unwrap();
             }
             HighlightMode::Wireframe => {
-                paint.set_style(Style::Stroke);
-                paint.set_stroke_width(cursor_height as f32 * 0.2);
-                canvas.draw_rect(&rect, &paint);
+                cr.rectangle(
+                    self.center_position.x - hr,
+                    self.center_position.y - hr,
+                    radius,
+                    radius,
+                );
+                cr.set_line_width(cursor_height * 0.2);
+                cr.stroke().unwrap();
             }
         }
+        cr.restore().unwrap();
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 struct ParticleData {
-    pos: Point,
-    speed: Point,
-    rotation_speed: f32,
-    lifetime: f32,
+    pos: Pos,
+    speed: Pos,
+    rotation_speed: f64,
+    lifetime: f64,
 }
 
+#[derive(Debug)]
 pub struct ParticleTrail {
     particles: Vec<ParticleData>,
-    previous_cursor_dest: Point,
+    previous_cursor_dest: Pos,
     trail_mode: TrailMode,
     rng: RngState,
 }
@@ -193,13 +180,13 @@ impl ParticleTrail {
     pub fn new(trail_mode: &TrailMode) -> ParticleTrail {
         ParticleTrail {
             particles: vec![],
-            previous_cursor_dest: Point::new(0.0, 0.0),
+            previous_cursor_dest: Pos::new(0.0, 0.0),
             trail_mode: trail_mode.clone(),
             rng: RngState::new(),
         }
     }
 
-    fn add_particle(&mut self, pos: Point, speed: Point, rotation_speed: f32, lifetime: f32) {
+    fn add_particle(&mut self, pos: Pos, speed: Pos, rotation_speed: f64, lifetime: f64) {
         self.particles.push(ParticleData {
             pos,
             speed,
@@ -219,9 +206,9 @@ impl CursorVfx for ParticleTrail {
     fn update(
         &mut self,
         settings: &CursorSettings,
-        current_cursor_dest: Point,
-        cursor_dimensions: Point,
-        dt: f32,
+        current_cursor_dest: Pos,
+        cursor_dimensions: Pos,
+        dt: f64,
     ) -> bool {
         // Update lifetimes and remove dead particles
         let mut i = 0;
@@ -238,7 +225,7 @@ impl CursorVfx for ParticleTrail {
         // Update particle positions
         for i in 0..self.particles.len() {
             let particle = &mut self.particles[i];
-            particle.pos += particle.speed * dt;
+            particle.pos = particle.pos + particle.speed * dt;
             particle.speed = rotate_vec(particle.speed, dt * particle.rotation_speed);
         }
 
@@ -248,21 +235,21 @@ impl CursorVfx for ParticleTrail {
             let travel_distance = travel.length();
 
             // Increase amount of particles when cursor travels further
-            let particle_count = ((travel_distance / cursor_dimensions.y as f32).powf(1.5)
+            let particle_count = ((travel_distance / cursor_dimensions.y).powf(1.5)
                 * settings.vfx_particle_density
                 * 0.01) as usize;
 
             let prev_p = self.previous_cursor_dest;
 
             for i in 0..particle_count {
-                let t = i as f32 / (particle_count as f32);
+                let t = i as f64 / (particle_count as f64);
 
                 let speed = match self.trail_mode {
                     TrailMode::Railgun => {
-                        let phase = t / std::f32::consts::PI
+                        let phase = t / std::f64::consts::PI
                             * settings.vfx_particle_phase
-                            * (travel_distance / cursor_dimensions.y as f32);
-                        Point::new(phase.sin(), phase.cos()) * 2.0 * settings.vfx_particle_speed
+                            * (travel_distance / cursor_dimensions.y);
+                        Pos::new(phase.sin(), phase.cos()) * 2.0 * settings.vfx_particle_speed
                     }
                     TrailMode::Torpedo => {
                         let mut travel_dir = travel;
@@ -273,7 +260,7 @@ impl CursorVfx for ParticleTrail {
                     }
                     TrailMode::PixieDust => {
                         let base_dir = self.rng.rand_dir_normalized();
-                        let dir = Point::new(base_dir.x * 0.5, 0.4 + base_dir.y.abs());
+                        let dir = Pos::new(base_dir.x * 0.5, 0.4 + base_dir.y.abs());
                         dir * 3.0 * settings.vfx_particle_speed
                     }
                 };
@@ -283,16 +270,16 @@ impl CursorVfx for ParticleTrail {
                     TrailMode::Railgun => prev_p + travel * t,
                     TrailMode::PixieDust | TrailMode::Torpedo => {
                         prev_p
-                            + travel * self.rng.next_f32()
-                            + Point::new(0.0, cursor_dimensions.y as f32 * 0.5)
+                            + travel * self.rng.next_f64()
+                            + Pos::new(0.0, cursor_dimensions.y * 0.5)
                     }
                 };
 
                 let rotation_speed = match self.trail_mode {
-                    TrailMode::Railgun => std::f32::consts::PI * settings.vfx_particle_curl,
+                    TrailMode::Railgun => std::f64::consts::PI * settings.vfx_particle_curl,
                     TrailMode::PixieDust | TrailMode::Torpedo => {
-                        (self.rng.next_f32() - 0.5)
-                            * std::f32::consts::FRAC_PI_2
+                        (self.rng.next_f64() - 0.5)
+                            * std::f64::consts::FRAC_PI_2
                             * settings.vfx_particle_curl
                     }
                 };
@@ -312,59 +299,54 @@ impl CursorVfx for ParticleTrail {
         !self.particles.is_empty()
     }
 
-    fn restart(&mut self, _position: Point) {}
+    fn restart(&mut self, _position: Pos) {}
 
-    fn render(
-        &self,
-        settings: &CursorSettings,
-        canvas: &mut Canvas,
-        grid_renderer: &mut GridRenderer,
-        cursor: &Cursor,
-    ) {
-        let mut paint = Paint::new(skia_safe::colors::WHITE, None);
-        let font_dimensions = grid_renderer.font_dimensions;
+    fn render(&self, settings: &CursorSettings, cr: &cairo::Context, cursor: &Cursor) {
+        let metrics = cursor.metrics.get();
+        let color = cursor.background();
+
+        cr.save().unwrap();
         match self.trail_mode {
             TrailMode::Torpedo | TrailMode::Railgun => {
-                paint.set_style(Style::Stroke);
-                paint.set_stroke_width(font_dimensions.height as f32 * 0.2);
+                cr.set_line_width(metrics.height() * 0.2);
             }
             _ => {}
         }
 
-        let colors = &grid_renderer.default_style.colors;
-        let base_color: Color = cursor.background(colors).to_color();
-
-        paint.set_blend_mode(BlendMode::SrcOver);
-
         self.particles.iter().for_each(|particle| {
             let lifetime = particle.lifetime / settings.vfx_particle_lifetime;
-            let alpha = (lifetime * settings.vfx_opacity) as u8;
-            let color = Color::from_argb(alpha, base_color.r(), base_color.g(), base_color.b());
-            paint.set_color(color);
+            let alpha = lifetime * settings.vfx_opacity / 255.;
+            cr.set_source_rgba(
+                color.red() as f64,
+                color.green() as f64,
+                color.blue() as f64,
+                alpha.max(0.).min(1.),
+            );
 
             let radius = match self.trail_mode {
-                TrailMode::Torpedo | TrailMode::Railgun => {
-                    font_dimensions.width as f32 * 0.5 * lifetime
-                }
-                TrailMode::PixieDust => font_dimensions.width as f32 * 0.2,
+                TrailMode::Torpedo | TrailMode::Railgun => metrics.width() * 0.5 * lifetime,
+                TrailMode::PixieDust => metrics.width() * 0.2,
             };
 
             let hr = radius * 0.5;
-            let rect = Rect::from_xywh(particle.pos.x - hr, particle.pos.y - hr, radius, radius);
 
             match self.trail_mode {
                 TrailMode::Torpedo | TrailMode::Railgun => {
-                    canvas.draw_oval(&rect, &paint);
+                    oval(cr, particle.pos, hr);
+                    cr.stroke().unwrap();
                 }
                 TrailMode::PixieDust => {
-                    canvas.draw_rect(&rect, &paint);
+                    cr.rectangle(particle.pos.x - hr, particle.pos.y - hr, radius, radius);
+                    cr.fill().unwrap();
                 }
             }
         });
+        cr.restore().unwrap();
     }
 }
 
 // Random number generator based on http://www.pcg-random.org/
+#[derive(Debug)]
 struct RngState {
     state: u64,
     inc: u64,
@@ -397,7 +379,7 @@ impl RngState {
         xsh.rotate_right(rot)
     }
 
-    fn next_f32(&mut self) -> f32 {
+    fn next_f64(&mut self) -> f64 {
         let v = self.next();
 
         // In C we'd do ldexp(v, -32) to bring a number in the range [0,2^32) down to [0,1) range.
@@ -414,28 +396,33 @@ impl RngState {
         // Build the new f64 value from the old mantissa and sign, and the new exponent
         let new_bits = (new_exponent << 52) | (float_bits & 0x801F_FFFF_FFFF_FFFFu64);
 
-        f64::from_bits(new_bits) as f32
+        f64::from_bits(new_bits)
     }
 
     // Produces a random vector with x and y in the [-1,1) range
     // Note: Vector is not normalized.
-    fn rand_dir(&mut self) -> Point {
-        let x = self.next_f32();
-        let y = self.next_f32();
+    fn rand_dir(&mut self) -> Pos {
+        let x = self.next_f64();
+        let y = self.next_f64();
 
-        Point::new(x * 2.0 - 1.0, y * 2.0 - 1.0)
+        Pos::new(x * 2.0 - 1.0, y * 2.0 - 1.0)
     }
 
-    fn rand_dir_normalized(&mut self) -> Point {
+    fn rand_dir_normalized(&mut self) -> Pos {
         let mut v = self.rand_dir();
         v.normalize();
         v
     }
 }
 
-fn rotate_vec(v: Point, rot: f32) -> Point {
+fn rotate_vec(v: Pos, rot: f64) -> Pos {
     let sin = rot.sin();
     let cos = rot.cos();
 
-    Point::new(v.x * cos - v.y * sin, v.x * sin + v.y * cos)
+    Pos::new(v.x * cos - v.y * sin, v.x * sin + v.y * cos)
+}
+
+fn oval(cr: &cairo::Context, center: Pos, radius: f64) {
+    cr.new_sub_path();
+    cr.arc(center.x, center.y, radius, 0., 2. * std::f64::consts::PI);
 }
@@ -186,6 +186,8 @@ where
                     let new_widgets = data.init_view(index, sender.clone());
                     let position = data.position(index);
                     let root = view.add(Data::root_widget(&new_widgets), &position);
+                    // apply state which needs the widget placed, like stacking.
+                    data.view(index, &new_widgets);
                     widgets.insert(
                         *index,
                         Widgets {
@@ -212,6 +214,7 @@ where
                     let new_widgets = data.init_view(index, sender.clone());
                     let position = data.position(index);
                     let root = view.add(Data::root_widget(&new_widgets), &position);
+                    data.view(index, &new_widgets);
                     widgets.insert(
                         *index,
                         Widgets {
@@ -1,6 +1,8 @@
+use std::ops::{Add, Mul, Sub};
+
 use relm4::factory::positions::FixedPosition;
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, PartialEq)]
 pub struct Coord {
     pub col: f64,
     pub row: f64,
@@ -21,7 +23,7 @@ impl From<(f64, f64)> for Coord {
     }
 }
 
-#[derive(Clone, Copy, Debug, Default)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
 pub struct Pos {
     pub x: f64,
     pub y: f64,
@@ -31,6 +33,48 @@ impl Pos {
     pub fn new(x: f64, y: f64) -> Pos {
         Pos { x, y }
     }
+
+    pub fn length(&self) -> f64 {
+        (self.x * self.x + self.y * self.y).sqrt()
+    }
+
+    /// scale to unit length, zero stays zero.
+    pub fn normalize(&mut self) {
+        let length = self.length();
+        if length > 0. {
+            self.x /= length;
+            self.y /= length;
+        }
+    }
+
+    pub fn dot(&self, other: Pos) -> f64 {
+        self.x * other.x + self.y * other.y
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.x == 0. && self.y == 0.
+    }
+}
+
+impl Add for Pos {
+    type Output = Pos;
+    fn add(self, other: Pos) -> Pos {
+        Pos::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl Sub for Pos {
+    type Output = Pos;
+    fn sub(self, other: Pos) -> Pos {
+        Pos::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl Mul<f64> for Pos {
+    type Output = Pos;
+    fn mul(self, scale: f64) -> Pos {
+        Pos::new(self.x * scale, self.y * scale)
+    }
 }
 
 impl Into<FixedPosition> for Pos {
@@ -0,0 +1,301 @@
+//! `guifont` option, `{font}[,{fallback}...][:{option}...]`.
+//!
+//! Spaces and commas in names are escaped by backslash, underscores stand for
+//! spaces. Options are `h{size}`, `w{width}`, `b`, `i`, `#e-{edging}` and
+//! `#h-{hinting}`.
+
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Edging {
+    Alias,
+    AntiAlias,
+    SubpixelAntiAlias,
+}
+
+impl From<Edging> for cairo::Antialias {
+    fn from(edging: Edging) -> Self {
+        match edging {
+            Edging::Alias => cairo::Antialias::None,
+            Edging::AntiAlias => cairo::Antialias::Gray,
+            Edging::SubpixelAntiAlias => cairo::Antialias::Subpixel,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Hinting {
+    Full,
+    Normal,
+    Slight,
+    None,
+}
+
+impl From<Hinting> for cairo::HintStyle {
+    fn from(hinting: Hinting) -> Self {
+        match hinting {
+            Hinting::Full => cairo::HintStyle::Full,
+            Hinting::Normal => cairo::HintStyle::Medium,
+            Hinting::Slight => cairo::HintStyle::Slight,
+            Hinting::None => cairo::HintStyle::None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum GuiFontError {
+    Size(String),
+    Width(String),
+    Edging(String),
+    Hinting(String),
+    Option(String),
+}
+
+impl fmt::Display for GuiFontError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GuiFontError::Size(size) => write!(f, "invalid size '{}'", size),
+            GuiFontError::Width(width) => write!(f, "invalid width '{}'", width),
+            GuiFontError::Edging(edging) => write!(
+                f,
+                "invalid edging '{}', expected alias, antialias or subpixelantialias",
+                edging
+            ),
+            GuiFontError::Hinting(hinting) => write!(
+                f,
+                "invalid hinting '{}', expected full, normal, slight or none",
+                hinting
+            ),
+            GuiFontError::Option(option) => write!(f, "unknown option '{}'", option),
+        }
+    }
+}
+
+impl std::error::Error for GuiFontError {}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GuiFont {
+    /// Font and its fallbacks, empty to keep the current ones.
+    pub families: Vec<String>,
+    /// Points.
+    pub size: Option<f64>,
+    /// Pixels added to width of cells.
+    pub width: Option<f64>,
+    pub bold: bool,
+    pub italic: bool,
+    pub edging: Option<Edging>,
+    pub hinting: Option<Hinting>,
+}
+
+impl GuiFont {
+    pub fn parse(guifont: &str) -> Result<GuiFont, GuiFontError> {
+        let mut segments = split_escaped(guifont, ':').into_iter();
+        let mut font = GuiFont {
+            families: segments
+                .next()
+                .map(|families| split_escaped(&families, ','))
+                .unwrap_or_default()
+                .iter()
+                .map(|family| unescape(family.trim()))
+                .filter(|family| !family.is_empty())
+                .collect(),
+            ..GuiFont::default()
+        };
+        for option in segments {
+            font.parse_option(unescape(option.trim()).trim())?;
+        }
+        Ok(font)
+    }
+
+    fn parse_option(&mut self, option: &str) -> Result<(), GuiFontError> {
+        if let Some(edging) = option.strip_prefix("#e-") {
+            self.edging.replace(match edging {
+                "alias" => Edging::Alias,
+                "antialias" => Edging::AntiAlias,
+                "subpixelantialias" => Edging::SubpixelAntiAlias,
+                _ => return Err(GuiFontError::Edging(edging.to_string())),
+            });
+        } else if let Some(hinting) = option.strip_prefix("#h-") {
+            self.hinting.replace(match hinting {
+                "full" => Hinting::Full,
+                "normal" => Hinting::Normal,
+                "slight" => Hinting::Slight,
+                "none" => Hinting::None,
+                _ => return Err(GuiFontError::Hinting(hinting.to_string())),
+            });
+        } else if let Some(size) = option.strip_prefix('h') {
+            match size.parse::<f64>() {
+                Ok(size) if size > 0. && size.is_finite() => self.size.replace(size),
+                _ => return Err(GuiFontError::Size(size.to_string())),
+            };
+        } else if let Some(width) = option.strip_prefix('w') {
+            match width.parse::<f64>() {
+                Ok(width) if width.is_finite() => self.width.replace(width),
+                _ => return Err(GuiFontError::Width(width.to_string())),
+            };
+        } else {
+            match option {
+                "b" => self.bold = true,
+                "i" => self.italic = true,
+                // vim options which make no sense here.
+                "" | "u" | "s" => {}
+                _ if option.starts_with('c') || option.starts_with('q') => {}
+                _ => return Err(GuiFontError::Option(option.to_string())),
+            }
+        }
+        Ok(())
+    }
+
+    /// `base` with family, size, weight and style of this font.
+    pub fn description(&self, base: &pango::FontDescription) -> pango::FontDescription {
+        let mut desc = base.clone();
+        if !self.families.is_empty() {
+            desc.set_family(&self.families.join(","));
+        }
+        if let Some(size) = self.size {
+            desc.set_size((size * pango::SCALE as f64).round() as i32);
+        }
+        desc.set_weight(if self.bold {
+            pango::Weight::Bold
+        } else {
+            pango::Weight::Normal
+        });
+        desc.set_style(if self.italic {
+            pango::Style::Italic
+        } else {
+            pango::Style::Normal
+        });
+        desc
+    }
+}
+
+impl FromStr for GuiFont {
+    type Err = GuiFontError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        GuiFont::parse(s)
+    }
+}
+
+// split by `sep` not escaped by backslash, escapes are kept.
+fn split_escaped(s: &str, sep: char) -> Vec<String> {
+    let mut parts = vec![String::new()];
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        let part = parts.last_mut().unwrap();
+        match c {
+            '\\' => {
+                part.push(c);
+                if let Some(c) = chars.next() {
+                    part.push(c);
+                }
+            }
+            c if c == sep => parts.push(String::new()),
+            c => part.push(c),
+        }
+    }
+    parts
+}
+
+fn unescape(s: &str) -> String {
+    let mut unescaped = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => unescaped.extend(chars.next()),
+            '_' => unescaped.push(' '),
+            c => unescaped.push(c),
+        }
+    }
+    unescaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_families() {
+        let font = GuiFont::parse("Cascadia Code,Symbols Nerd Font Mono Light:h12").unwrap();
+        assert_eq!(
+            font.families,
+            vec!["Cascadia Code", "Symbols Nerd Font Mono Light"]
+        );
+        assert_eq!(font.size, Some(12.));
+
+        let font = GuiFont::parse("Fira\\ Code, Noto_Color_Emoji ,").unwrap();
+        assert_eq!(font.families, vec!["Fira Code", "Noto Color Emoji"]);
+        assert_eq!(font.size, None);
+
+        let font = GuiFont::parse("Foo\\,Bar\\:Baz\\_Qux").unwrap();
+        assert_eq!(font.families, vec!["Foo,Bar:Baz_Qux"]);
+
+        let font = GuiFont::parse(":h14").unwrap();
+        assert!(font.families.is_empty());
+        assert_eq!(font.size, Some(14.));
+    }
+
+    #[test]
+    fn test_parse_options() {
+        let font =
+            GuiFont::parse("Iosevka:h10.5:w-0.5:b:i:#e-subpixelantialias:#h-slight").unwrap();
+        assert_eq!(
+            font,
+            GuiFont {
+                families: vec!["Iosevka".to_string()],
+                size: Some(10.5),
+                width: Some(-0.5),
+                bold: true,
+                italic: true,
+                edging: Some(Edging::SubpixelAntiAlias),
+                hinting: Some(Hinting::Slight),
+            }
+        );
+
+        let font = GuiFont::parse("Iosevka:h11:cANSI:qDRAFT::").unwrap();
+        assert_eq!(font.size, Some(11.));
+        assert!(!font.bold && !font.italic);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            GuiFont::parse("Iosevka:hbig"),
+            Err(GuiFontError::Size("big".to_string()))
+        );
+        assert_eq!(
+            GuiFont::parse("Iosevka:h0"),
+            Err(GuiFontError::Size("0".to_string()))
+        );
+        assert_eq!(
+            GuiFont::parse("Iosevka:#e-smooth"),
+            Err(GuiFontError::Edging("smooth".to_string()))
+        );
+        assert_eq!(
+            GuiFont::parse("Iosevka:#h-max"),
+            Err(GuiFontError::Hinting("max".to_string()))
+        );
+        assert_eq!(
+            GuiFont::parse("Iosevka:x"),
+            Err(GuiFontError::Option("x".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_description() {
+        let base = pango::FontDescription::from_string("monospace 11");
+        let desc = GuiFont::parse("Fira Code,Noto Color Emoji:h12.5:b")
+            .unwrap()
+            .description(&base);
+        assert_eq!(desc.family().unwrap(), "Fira Code,Noto Color Emoji");
+        assert_eq!(desc.size(), (12.5 * pango::SCALE as f64) as i32);
+        assert_eq!(desc.weight(), pango::Weight::Bold);
+        assert_eq!(desc.style(), pango::Style::Normal);
+
+        let desc = GuiFont::parse(":i").unwrap().description(&base);
+        assert_eq!(desc.family().unwrap(), "monospace");
+        assert_eq!(desc.size(), 11 * pango::SCALE);
+        assert_eq!(desc.style(), pango::Style::Italic);
+    }
+}
@@ -5,6 +5,9 @@ extern crate derivative;
 
 use clap::{IntoApp, Parser};
 
+use settings::SettingGroup;
+
+mod animation;
 mod app;
 mod bridge;
 mod color;
@@ -13,11 +16,14 @@ mod cursor;
 mod event_aggregator;
 mod factory;
 mod grapheme;
+mod guifont;
 mod keys;
 mod loggingchan;
 mod messager;
 mod metrics;
+mod minimap;
 mod running_tracker;
+mod scheduler;
 mod settings;
 mod style;
 mod vimview;
@@ -89,6 +95,14 @@ fn main() {
     let title = app.get_bin_name().unwrap_or("rv");
     opts.title = title.to_string();
     log::trace!("opts: {:?}", opts);
+
+    cursor::CursorSettings::default().register();
+    vimview::ScrollSettings::default().register();
+    vimview::WindowSettings::default().register();
+    vimview::FontSettings::default().register();
+    scheduler::FrameSettings::default().register();
+    minimap::MinimapSettings::default().register();
+
     let model = app::AppModel::new(opts);
     let relm = relm4::RelmApp::new(model);
 
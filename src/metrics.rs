@@ -12,6 +12,9 @@ pub struct Metrics {
     /// by pango font metrics
     width: f64,
     ascent: f64,
+    /// by pango font metrics, below baseline.
+    underline_position: f64,
+    underline_thickness: f64,
 }
 
 impl Metrics {
@@ -26,6 +29,9 @@ impl Metrics {
             width: 1.,
             height: 2.,
             ascent: 0.,
+
+            underline_position: 1.,
+            underline_thickness: 1.,
         }
     }
 
@@ -76,4 +82,21 @@ impl Metrics {
     pub fn set_ascent(&mut self, ascent: f64) {
         self.ascent = ascent;
     }
+
+    /// distance of top of underline below baseline.
+    pub fn underline_position(&self) -> f64 {
+        self.underline_position
+    }
+
+    pub fn set_underline_position(&mut self, underline_position: f64) {
+        self.underline_position = underline_position;
+    }
+
+    pub fn underline_thickness(&self) -> f64 {
+        self.underline_thickness
+    }
+
+    pub fn set_underline_thickness(&mut self, underline_thickness: f64) {
+        self.underline_thickness = underline_thickness;
+    }
 }
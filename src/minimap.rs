@@ -0,0 +1,500 @@
+//! Zoomed-out buffer of the current window, drawn in a sidebar right of the
+//! grids.
+
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use std::time::Duration;
+
+use gtk::prelude::*;
+use nvim::Value;
+use parking_lot::RwLock;
+use relm4::{MicroModel, MicroWidgets, Sender};
+
+use crate::bridge::{BufferHighlight, ParallelCommand, UiCommand};
+use crate::color::Color;
+use crate::event_aggregator::EVENT_AGGREGATOR;
+use crate::scheduler::scheduler;
+use crate::settings::{setting_group, SETTINGS};
+use crate::vimview::HighlightDefinitions;
+
+// pixels of one line and one column.
+const LINE_HEIGHT: f64 = 2.;
+const COLUMN_WIDTH: f64 = 1.;
+const TAB_WIDTH: usize = 4;
+// milliseconds without edits before refetching highlights.
+const HIGHLIGHTS_DELAY: u64 = 150;
+
+setting_group! {
+    pub struct MinimapSettings("minimap") {
+        enabled: bool = false,
+        // pixels.
+        width: u64 = 100,
+    }
+}
+
+#[derive(Debug, Default)]
+struct Line {
+    text: String,
+    // byte columns {start}..{end} of text.
+    highlights: Vec<(usize, usize, Color)>,
+}
+
+#[derive(Debug, Default)]
+struct Content {
+    window: Option<Value>,
+    buffer: Option<Value>,
+    lines: Vec<Line>,
+    // top and bottom line of window.
+    viewport: (f64, f64),
+}
+
+impl Content {
+    /// First line drawn with {rows} lines fitting, following the viewport.
+    fn first_line(&self, rows: usize) -> usize {
+        let count = self.lines.len();
+        if count <= rows {
+            return 0;
+        }
+        let (top, bottom) = self.viewport;
+        let scrollable = (count as f64 - (bottom - top)).max(1.);
+        ((count - rows) as f64 * (top / scrollable).clamp(0., 1.)).round() as usize
+    }
+
+    /// Top line of window centered at {y} pixels of minimap {rows} high,
+    /// the last screen of lines stays in view.
+    fn top_line(&self, y: f64, rows: usize) -> u64 {
+        let (top, bottom) = self.viewport;
+        let height = bottom - top;
+        let line = self.first_line(rows) as f64 + y / LINE_HEIGHT - height / 2.;
+        let last = self.lines.len().saturating_sub(height as usize);
+        line.clamp(0., last as f64).round() as u64
+    }
+
+    fn set_highlights(&mut self, highlights: Vec<BufferHighlight>) {
+        for highlight in highlights {
+            if let Some(line) = self.lines.get_mut(highlight.line as usize) {
+                let start = highlight.start_column as usize;
+                let end = highlight
+                    .end_column
+                    .map(|end| end as usize)
+                    .unwrap_or(usize::MAX);
+                line.highlights.push((start, end, highlight.color));
+            }
+        }
+    }
+}
+
+pub struct VimMinimap {
+    hldefs: Rc<RwLock<HighlightDefinitions>>,
+    content: Rc<RefCell<Content>>,
+    // (grid, window) whose buffer is attached or being attached.
+    watching: Option<(u64, Value)>,
+    // waiting for reply of attaching.
+    pending: bool,
+    visible: bool,
+}
+
+impl VimMinimap {
+    pub fn new(hldefs: Rc<RwLock<HighlightDefinitions>>) -> VimMinimap {
+        VimMinimap {
+            hldefs,
+            content: Rc::new(RefCell::new(Content::default())),
+            watching: None,
+            pending: false,
+            visible: false,
+        }
+    }
+
+    pub fn buffer(&self) -> Option<Value> {
+        self.content.borrow().buffer.clone()
+    }
+
+    /// Follow window showing lines {top}..{bottom}.
+    pub fn set_viewport(&mut self, top: f64, bottom: f64) {
+        self.visible = true;
+        self.content.borrow_mut().viewport = (top, bottom);
+    }
+
+    pub fn hide(&mut self) {
+        self.visible = false;
+    }
+
+    /// Watch buffer of {window} on {grid}, returns false if it is watched
+    /// already or attaching is pending.
+    pub fn watch(&mut self, grid: u64, window: &Value) -> bool {
+        let target = (grid, window.clone());
+        if self.pending || self.watching.as_ref() == Some(&target) {
+            return false;
+        }
+        self.watching.replace(target);
+        self.pending = true;
+        true
+    }
+
+    /// Attaching for {grid} finished with the buffer unchanged, shown in
+    /// {window}, none if attaching failed.
+    pub fn set_watched(&mut self, grid: u64, window: Option<Value>) {
+        self.pending = false;
+        match window {
+            Some(window) => {
+                self.content.borrow_mut().window.replace(window);
+            }
+            None if self.watching.as_ref().map(|(g, _)| *g) == Some(grid) => {
+                self.watching = None;
+            }
+            None => {}
+        }
+    }
+
+    /// Watch again when followed next time, window may show another buffer.
+    pub fn rewatch(&mut self) {
+        self.watching = None;
+    }
+
+    /// Stop watching, returns buffer to detach.
+    pub fn unwatch(&mut self) -> Option<Value> {
+        self.watching = None;
+        std::mem::take(&mut *self.content.borrow_mut()).buffer
+    }
+
+    /// {buffer} detached by nvim, like unloaded.
+    pub fn set_detached(&mut self, buffer: &Value) {
+        let mut content = self.content.borrow_mut();
+        if content.buffer.as_ref() == Some(buffer) {
+            *content = Content::default();
+            self.watching = None;
+        }
+    }
+
+    /// {buffer} of {window} is being attached, lines arrive by
+    /// [`VimMinimap::set_lines`].
+    pub fn set_buffer(&mut self, window: Value, buffer: Value) {
+        self.pending = false;
+        let mut content = self.content.borrow_mut();
+        content.window.replace(window);
+        content.buffer.replace(buffer);
+        content.lines.clear();
+    }
+
+    /// Replace lines {first}..{last} of {buffer}, returns false for other
+    /// buffers.
+    pub fn set_lines(
+        &mut self,
+        buffer: &Value,
+        first: u64,
+        last: Option<u64>,
+        lines: Vec<String>,
+    ) -> bool {
+        let mut content = self.content.borrow_mut();
+        if content.buffer.as_ref() != Some(buffer) {
+            return false;
+        }
+        let count = content.lines.len();
+        let first = (first as usize).min(count);
+        let last = last
+            .map(|last| last as usize)
+            .unwrap_or(count)
+            .clamp(first, count);
+        // replaced lines keep their colors until highlights are refetched.
+        let mut replaced = content.lines[first..last]
+            .iter_mut()
+            .map(|line| std::mem::take(&mut line.highlights))
+            .collect::<Vec<_>>()
+            .into_iter();
+        content.lines.splice(
+            first..last,
+            lines.into_iter().map(|text| Line {
+                text,
+                highlights: replaced.next().unwrap_or_default(),
+            }),
+        );
+        true
+    }
+
+    /// Refetch highlights of the whole buffer once edits pause, treesitter
+    /// may recolor lines far from the edited ones.
+    pub fn refresh_highlights(&self) {
+        let buffer = match self.buffer() {
+            Some(buffer) => buffer,
+            None => return,
+        };
+        scheduler().timeout(
+            "minimap-highlights",
+            Duration::from_millis(HIGHLIGHTS_DELAY),
+            move || {
+                EVENT_AGGREGATOR.send(UiCommand::Parallel(ParallelCommand::BufferHighlights {
+                    buffer: buffer.clone(),
+                }));
+                None
+            },
+        );
+    }
+
+    /// Replace highlights of all lines of {buffer}.
+    pub fn set_highlights(&mut self, buffer: &Value, highlights: Vec<BufferHighlight>) {
+        let mut content = self.content.borrow_mut();
+        if content.buffer.as_ref() != Some(buffer) {
+            return;
+        }
+        content
+            .lines
+            .iter_mut()
+            .for_each(|line| line.highlights.clear());
+        content.set_highlights(highlights);
+    }
+}
+
+impl MicroModel for VimMinimap {
+    type Msg = ();
+    type Widgets = MinimapWidgets;
+    type Data = ();
+
+    fn update(&mut self, _: (), _data: &(), _sender: Sender<Self::Msg>) {}
+}
+
+#[derive(Debug)]
+pub struct MinimapWidgets {
+    da: gtk::DrawingArea,
+}
+
+impl MicroWidgets<VimMinimap> for MinimapWidgets {
+    type Root = gtk::DrawingArea;
+
+    fn init_view(model: &VimMinimap, _sender: Sender<()>) -> Self {
+        let da = gtk::DrawingArea::new();
+        da.set_widget_name("minimap");
+        da.set_visible(false);
+        da.set_hexpand(false);
+        da.set_vexpand(true);
+        da.set_can_focus(false);
+        da.set_focus_on_click(false);
+
+        let content = model.content.clone();
+        let hldefs = model.hldefs.clone();
+        da.set_draw_func(move |_da, cr, width, height| {
+            let content = content.borrow();
+            let hldefs = hldefs.read();
+            let colors = match hldefs.defaults() {
+                Some(colors) => *colors,
+                None => return,
+            };
+            let (width, height) = (width as f64, height as f64);
+            if let Some(bg) = colors.background {
+                cr.set_source_rgba(bg.red() as _, bg.green() as _, bg.blue() as _, 0.9);
+                cr.rectangle(0., 0., width, height);
+                cr.fill().unwrap();
+            }
+            let fg = match colors.foreground {
+                Some(fg) => fg,
+                None => return,
+            };
+
+            let rows = (height / LINE_HEIGHT) as usize;
+            let first = content.first_line(rows);
+            let columns = (width / COLUMN_WIDTH) as usize;
+            for (row, line) in content.lines.iter().skip(first).take(rows).enumerate() {
+                let y = row as f64 * LINE_HEIGHT;
+                let mut column = 0;
+                for (index, c) in line.text.char_indices() {
+                    if column >= columns {
+                        break;
+                    }
+                    let cells = if c == '\t' { TAB_WIDTH } else { 1 };
+                    if !c.is_whitespace() {
+                        let color = line
+                            .highlights
+                            .iter()
+                            .rev()
+                            .find(|(start, end, _)| *start <= index && index < *end)
+                            .map(|(_, _, color)| color)
+                            .unwrap_or(&fg);
+                        cr.set_source_rgba(
+                            color.red() as _,
+                            color.green() as _,
+                            color.blue() as _,
+                            0.7,
+                        );
+                        cr.rectangle(
+                            column as f64 * COLUMN_WIDTH,
+                            y,
+                            COLUMN_WIDTH,
+                            LINE_HEIGHT - 0.5,
+                        );
+                        cr.fill().unwrap();
+                    }
+                    column += cells;
+                }
+            }
+
+            let (top, bottom) = content.viewport;
+            cr.set_source_rgba(fg.red() as _, fg.green() as _, fg.blue() as _, 0.15);
+            cr.rectangle(
+                0.,
+                (top - first as f64) * LINE_HEIGHT,
+                width,
+                (bottom - top) * LINE_HEIGHT,
+            );
+            cr.fill().unwrap();
+        });
+
+        // centers viewport on the line under pointer.
+        let scroll_to = glib::clone!(@strong model.content as content => move |y: f64, last: &Cell<Option<u64>>, height: f64| {
+            let content = content.borrow();
+            let window = match content.window {
+                Some(ref window) => window.clone(),
+                None => return,
+            };
+            let top_line = content.top_line(y, (height / LINE_HEIGHT) as usize);
+            if last.replace(Some(top_line)) != Some(top_line) {
+                EVENT_AGGREGATOR.send(UiCommand::Parallel(ParallelCommand::ScrollWindow {
+                    window,
+                    top_line,
+                }));
+            }
+        });
+        let last = Rc::new(Cell::new(None));
+        let drag_listener = gtk::GestureDrag::builder()
+            .button(1)
+            .name("minimap-drag-listener")
+            .build();
+        drag_listener.connect_drag_begin(
+            glib::clone!(@strong scroll_to, @strong last => move |c, _, y| {
+                let height = c.widget().height() as f64;
+                last.set(None);
+                scroll_to(y, &last, height);
+            }),
+        );
+        drag_listener.connect_drag_update(
+            glib::clone!(@strong scroll_to, @strong last => move |c, _, y| {
+                let height = c.widget().height() as f64;
+                if let Some((_, start_y)) = c.start_point() {
+                    scroll_to(start_y + y, &last, height);
+                }
+            }),
+        );
+        da.add_controller(&drag_listener);
+
+        MinimapWidgets { da }
+    }
+
+    fn view(&mut self, model: &VimMinimap, _: Sender<()>) {
+        let settings = SETTINGS.get::<MinimapSettings>();
+        let visible = settings.enabled && model.visible && model.buffer().is_some();
+        self.da.set_visible(visible);
+        if !visible {
+            return;
+        }
+        // grids shrink to leave the column to the minimap.
+        self.da.set_size_request(settings.width as i32, -1);
+        self.da.queue_draw();
+    }
+
+    fn root_widget(&self) -> Self::Root {
+        self.da.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn content(count: usize, viewport: (f64, f64)) -> Content {
+        Content {
+            lines: (0..count).map(|_| Line::default()).collect(),
+            viewport,
+            ..Content::default()
+        }
+    }
+
+    fn texts(minimap: &VimMinimap) -> Vec<String> {
+        let content = minimap.content.borrow();
+        content.lines.iter().map(|line| line.text.clone()).collect()
+    }
+
+    #[test]
+    fn test_first_line() {
+        // all lines fit.
+        assert_eq!(content(10, (4., 8.)).first_line(20), 0);
+        // follows the viewport from top to bottom of buffer.
+        assert_eq!(content(100, (0., 10.)).first_line(50), 0);
+        assert_eq!(content(100, (45., 55.)).first_line(50), 25);
+        assert_eq!(content(100, (90., 100.)).first_line(50), 50);
+        assert_eq!(content(100, (200., 210.)).first_line(50), 50);
+    }
+
+    #[test]
+    fn test_top_line() {
+        // pointer at line 30 centers the window of 10 lines on it.
+        assert_eq!(content(100, (0., 10.)).top_line(30. * LINE_HEIGHT, 200), 25);
+        assert_eq!(content(100, (0., 10.)).top_line(0., 200), 0);
+        // does not scroll past the last screen of lines.
+        assert_eq!(content(100, (0., 10.)).top_line(99. * LINE_HEIGHT, 200), 90);
+        assert_eq!(content(5, (0., 10.)).top_line(4. * LINE_HEIGHT, 200), 0);
+    }
+
+    #[test]
+    fn test_highlights() {
+        let buffer = Value::from(1);
+        let color = Color::new(1., 0., 0., 1.);
+        let mut minimap = VimMinimap::new(Rc::new(RwLock::new(HighlightDefinitions::new())));
+        minimap.set_buffer(Value::from(1000), buffer.clone());
+        let lines = ["a", "b"].iter().map(|s| s.to_string()).collect();
+        minimap.set_lines(&buffer, 0, None, lines);
+        let highlight = |line| BufferHighlight {
+            line,
+            start_column: 0,
+            end_column: None,
+            color,
+        };
+        minimap.set_highlights(&buffer, vec![highlight(0), highlight(1)]);
+
+        // edited line keeps its colors until refetched, inserted ones have none.
+        let lines = vec!["x".to_string(), "y".to_string()];
+        minimap.set_lines(&buffer, 1, Some(2), lines);
+        let counts = |minimap: &VimMinimap| {
+            let content = minimap.content.borrow();
+            content
+                .lines
+                .iter()
+                .map(|line| line.highlights.len())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(counts(&minimap), [1, 1, 0]);
+
+        // refetched highlights replace all.
+        minimap.set_highlights(&buffer, vec![highlight(2)]);
+        assert_eq!(counts(&minimap), [0, 0, 1]);
+    }
+
+    #[test]
+    fn test_set_lines() {
+        let buffer = Value::from(1);
+        let mut minimap = VimMinimap::new(Rc::new(RwLock::new(HighlightDefinitions::new())));
+        minimap.set_buffer(Value::from(1000), buffer.clone());
+
+        // other buffers are ignored.
+        assert!(!minimap.set_lines(&Value::from(2), 0, None, Vec::new()));
+
+        // first lines event of attaching sends the whole buffer.
+        let lines = ["a", "b", "c", "d"].iter().map(|s| s.to_string()).collect();
+        assert!(minimap.set_lines(&buffer, 0, None, lines));
+        assert_eq!(texts(&minimap), ["a", "b", "c", "d"]);
+
+        // replace "b", "c" with three lines.
+        let lines = vec!["x".to_string(), "y".to_string(), "z".to_string()];
+        assert!(minimap.set_lines(&buffer, 1, Some(3), lines));
+        assert_eq!(texts(&minimap), ["a", "x", "y", "z", "d"]);
+
+        // delete "y", "z".
+        assert!(minimap.set_lines(&buffer, 2, Some(4), Vec::new()));
+        assert_eq!(texts(&minimap), ["a", "x", "d"]);
+
+        // append at end, out of range last clamped.
+        assert!(minimap.set_lines(&buffer, 3, Some(10), vec!["e".to_string()]));
+        assert_eq!(texts(&minimap), ["a", "x", "d", "e"]);
+
+        // replace to the end of buffer.
+        assert!(minimap.set_lines(&buffer, 1, None, vec!["f".to_string()]));
+        assert_eq!(texts(&minimap), ["a", "f"]);
+    }
+}
@@ -0,0 +1,335 @@
+//! Cursor blinking, pointer hiding and animations share one tick callback
+//! on the main toplevel, which pauses while no toplevel of the application is
+//! focused, or the focused one is minimized or hidden.
+
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+
+use gtk::gdk;
+use gtk::prelude::*;
+
+use crate::settings::{setting_group, SETTINGS};
+
+// seconds passed to animations to finish them at once.
+const FINISH_DT: f64 = 1000.;
+// microseconds of frame assumed before the first tick.
+const FRAME_TIME: i64 = 1_000_000 / 60;
+
+setting_group! {
+    pub struct FrameSettings("") {
+        // frames per second, zero follows the display.
+        refresh_rate: u64 = 0,
+    }
+}
+
+enum Task {
+    // run at frame time {due}, returns delay to run again.
+    Timeout {
+        due: i64,
+        callback: Box<dyn FnMut() -> Option<Duration>>,
+    },
+    // run every frame with seconds since last frame, until returns false.
+    Animation {
+        callback: Box<dyn FnMut(f64) -> bool>,
+    },
+}
+
+struct Job {
+    name: Cow<'static, str>,
+    task: Task,
+}
+
+#[derive(Default)]
+struct State {
+    window: Option<gtk::Window>,
+    jobs: Vec<Job>,
+    // jobs are running, taken out of {jobs}.
+    in_tick: bool,
+    // cancelled while jobs running.
+    cancelled: Vec<Cow<'static, str>>,
+    tick: Option<gtk::TickCallbackId>,
+    wakeup: Option<glib::SourceId>,
+    // frame time of last run, in microseconds.
+    last_frame: Option<i64>,
+    paused: bool,
+}
+
+impl State {
+    fn needs_tick(&self, now: i64) -> bool {
+        self.jobs.iter().any(|job| match job.task {
+            Task::Animation { .. } => true,
+            Task::Timeout { due, .. } => due - now <= FRAME_TIME,
+        })
+    }
+
+    fn next_due(&self) -> Option<i64> {
+        self.jobs
+            .iter()
+            .filter_map(|job| match job.task {
+                Task::Timeout { due, .. } => Some(due),
+                Task::Animation { .. } => None,
+            })
+            .min()
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct Scheduler(Rc<RefCell<State>>);
+
+thread_local! {
+    static SCHEDULER: Scheduler = Scheduler::default();
+}
+
+/// Scheduler of gtk main thread.
+pub fn scheduler() -> Scheduler {
+    SCHEDULER.with(|scheduler| scheduler.clone())
+}
+
+impl Scheduler {
+    /// Run jobs on frame clock of `window`.
+    pub fn attach(&self, window: &gtk::Window) {
+        self.0.borrow_mut().window.replace(window.clone());
+        self.watch(window);
+    }
+
+    /// Keep running while `window` is active, like toplevels of external
+    /// windows.
+    pub fn watch(&self, window: &gtk::Window) {
+        let watch_state = glib::clone!(@strong self as this => move |window: &gtk::Window| {
+            if let Some(toplevel) = window
+                .surface()
+                .and_then(|surface| surface.downcast::<gdk::Toplevel>().ok())
+            {
+                toplevel.connect_state_notify(glib::clone!(@strong this => move |_| {
+                    this.update_paused();
+                }));
+            }
+        });
+        if window.is_realized() {
+            watch_state(window);
+        }
+        window.connect_realize(watch_state);
+        window.connect_is_active_notify(glib::clone!(@strong self as this => move |_| {
+            // focus moving between toplevels deactivates one before activating the other.
+            glib::idle_add_local_once(glib::clone!(@strong this => move || {
+                this.update_paused();
+            }));
+        }));
+        window.connect_map(glib::clone!(@strong self as this => move |_| {
+            this.update_paused();
+        }));
+        window.connect_unmap(glib::clone!(@strong self as this => move |_| {
+            this.update_paused();
+        }));
+        self.update_paused();
+    }
+
+    /// Call `callback` on the first frame after `delay`, again after the
+    /// returned delay. Replaces the job with same `name`.
+    pub fn timeout<N, F>(&self, name: N, delay: Duration, callback: F)
+    where
+        N: Into<Cow<'static, str>>,
+        F: FnMut() -> Option<Duration> + 'static,
+    {
+        let due = glib::monotonic_time() + delay.as_micros() as i64;
+        self.push(Job {
+            name: name.into(),
+            task: Task::Timeout {
+                due,
+                callback: Box::new(callback),
+            },
+        });
+    }
+
+    /// Call `callback` with seconds since last frame on every frame until it
+    /// returns false, at once to finish while paused. Replaces the job with
+    /// same `name`.
+    pub fn animate<N, F>(&self, name: N, mut callback: F)
+    where
+        N: Into<Cow<'static, str>>,
+        F: FnMut(f64) -> bool + 'static,
+    {
+        let name = name.into();
+        if self.0.borrow().paused {
+            self.cancel(name.clone());
+            callback(FINISH_DT);
+            return;
+        }
+        self.push(Job {
+            name,
+            task: Task::Animation {
+                callback: Box::new(callback),
+            },
+        });
+    }
+
+    pub fn cancel<N: Into<Cow<'static, str>>>(&self, name: N) {
+        let name = name.into();
+        let mut state = self.0.borrow_mut();
+        state.jobs.retain(|job| job.name != name);
+        // running jobs are put back after the tick, unless cancelled.
+        if state.in_tick && !state.cancelled.contains(&name) {
+            state.cancelled.push(name);
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.0.borrow().paused
+    }
+
+    fn push(&self, job: Job) {
+        {
+            let mut state = self.0.borrow_mut();
+            state.jobs.retain(|j| j.name != job.name);
+            state.jobs.push(job);
+        }
+        self.reschedule();
+    }
+
+    fn update_paused(&self) {
+        // paused unless any toplevel is in use.
+        let paused = self.0.borrow().window.is_none()
+            || !gtk::Window::list_toplevels()
+                .iter()
+                .filter_map(|widget| widget.downcast_ref::<gtk::Window>())
+                .any(|window| {
+                    let minimized = window
+                        .surface()
+                        .and_then(|surface| surface.downcast::<gdk::Toplevel>().ok())
+                        .map(|toplevel| toplevel.state().contains(gdk::ToplevelState::MINIMIZED))
+                        .unwrap_or(false);
+                    window.is_active() && window.is_mapped() && !minimized
+                });
+        if self.0.borrow().paused == paused {
+            return;
+        }
+        log::debug!("scheduler {}", if paused { "paused" } else { "resumed" });
+        self.0.borrow_mut().paused = paused;
+        if paused {
+            self.stop();
+            self.finish_animations();
+        } else {
+            self.reschedule();
+        }
+    }
+
+    fn finish_animations(&self) {
+        let jobs = std::mem::take(&mut self.0.borrow_mut().jobs);
+        let mut timeouts = Vec::new();
+        for job in jobs {
+            match job.task {
+                Task::Animation { mut callback } => {
+                    callback(FINISH_DT);
+                }
+                Task::Timeout { .. } => timeouts.push(job),
+            }
+        }
+        let mut state = self.0.borrow_mut();
+        timeouts.extend(state.jobs.drain(..));
+        state.jobs = timeouts;
+    }
+
+    fn stop(&self) {
+        let mut state = self.0.borrow_mut();
+        if let Some(tick) = state.tick.take() {
+            tick.remove();
+        }
+        if let Some(wakeup) = state.wakeup.take() {
+            wakeup.remove();
+        }
+        state.last_frame = None;
+    }
+
+    /// Tick on frame clock while something is due, sleep until next timeout
+    /// otherwise.
+    fn reschedule(&self) {
+        let mut state = self.0.borrow_mut();
+        if state.paused || state.tick.is_some() {
+            return;
+        }
+        let window = match state.window {
+            Some(ref window) => window.clone(),
+            None => return,
+        };
+        if let Some(wakeup) = state.wakeup.take() {
+            wakeup.remove();
+        }
+        let now = glib::monotonic_time();
+        if state.needs_tick(now) {
+            let this = self.clone();
+            let tick = window.add_tick_callback(move |_, clock| this.tick(clock.frame_time()));
+            state.tick.replace(tick);
+        } else if let Some(due) = state.next_due() {
+            let this = self.clone();
+            let delay = Duration::from_micros((due - now - FRAME_TIME).max(0) as u64);
+            let wakeup = glib::timeout_add_local_once(delay, move || {
+                this.0.borrow_mut().wakeup = None;
+                this.reschedule();
+            });
+            state.wakeup.replace(wakeup);
+        }
+    }
+
+    fn tick(&self, now: i64) -> glib::Continue {
+        let refresh_rate = SETTINGS.get::<FrameSettings>().refresh_rate;
+        let last_frame = self.0.borrow().last_frame;
+        if let Some(last) = last_frame {
+            // a little early is fine, frames never come exactly on time.
+            if refresh_rate > 0 && now - last < 900_000 / refresh_rate as i64 {
+                return glib::Continue(true);
+            }
+        }
+        let dt = (now - last_frame.unwrap_or(now - FRAME_TIME)) as f64 / 1_000_000.;
+        self.0.borrow_mut().last_frame.replace(now);
+
+        // jobs may schedule more jobs, so they run without borrowing state.
+        let jobs = {
+            let mut state = self.0.borrow_mut();
+            state.in_tick = true;
+            std::mem::take(&mut state.jobs)
+        };
+        let mut kept = Vec::with_capacity(jobs.len());
+        for mut job in jobs {
+            let keep = match job.task {
+                Task::Timeout {
+                    ref mut due,
+                    ref mut callback,
+                } => {
+                    if *due > now {
+                        true
+                    } else if let Some(delay) = callback() {
+                        *due = now + delay.as_micros() as i64;
+                        true
+                    } else {
+                        false
+                    }
+                }
+                Task::Animation { ref mut callback } => callback(dt),
+            };
+            if keep {
+                kept.push(job);
+            }
+        }
+
+        let mut state = self.0.borrow_mut();
+        let added = std::mem::take(&mut state.jobs);
+        let cancelled = std::mem::take(&mut state.cancelled);
+        state.in_tick = false;
+        kept.retain(|job| {
+            !cancelled.contains(&job.name) && added.iter().all(|j| j.name != job.name)
+        });
+        kept.extend(added);
+        state.jobs = kept;
+
+        if !state.paused && state.needs_tick(now) {
+            return glib::Continue(true);
+        }
+        state.tick = None;
+        state.last_frame = None;
+        drop(state);
+        self.reschedule();
+        glib::Continue(false)
+    }
+}
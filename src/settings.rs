@@ -12,10 +12,153 @@ use crate::bridge::TxWrapper;
 
 pub static SETTINGS: Lazy<Settings> = Lazy::new(|| Settings::new());
 
+/// Settings are read from and written to `g:rv_{name}`.
+pub const VARIABLE_PREFIX: &str = "rv_";
+/// Settings are still read from `g:neovide_{name}` if `g:rv_{name}` is unset.
+pub const LEGACY_VARIABLE_PREFIX: &str = "neovide_";
+
+/// Value of `g:rv_{name}`, or of `g:neovide_{name}` written by older configs.
+pub async fn get_var(
+    nvim: &Neovim<TxWrapper>,
+    name: &str,
+) -> Result<Value, Box<nvim::error::CallError>> {
+    match nvim.get_var(&format!("{}{}", VARIABLE_PREFIX, name)).await {
+        Ok(value) => Ok(value),
+        Err(error) => nvim
+            .get_var(&format!("{}{}", LEGACY_VARIABLE_PREFIX, name))
+            .await
+            .map_err(|_| error),
+    }
+}
+
 pub trait SettingGroup {
     fn register(&self);
 }
 
+pub trait ParseFromValue {
+    fn parse_from_value(&mut self, value: Value);
+}
+
+impl ParseFromValue for f32 {
+    fn parse_from_value(&mut self, value: Value) {
+        if let Some(v) = value.as_f64() {
+            *self = v as f32;
+        } else if let Some(v) = value.as_i64() {
+            *self = v as f32;
+        } else {
+            log::error!("Setting expected a f32, but received {:?}", value);
+        }
+    }
+}
+
+impl ParseFromValue for f64 {
+    fn parse_from_value(&mut self, value: Value) {
+        if let Some(v) = value.as_f64() {
+            *self = v;
+        } else if let Some(v) = value.as_i64() {
+            *self = v as f64;
+        } else {
+            log::error!("Setting expected a f64, but received {:?}", value);
+        }
+    }
+}
+
+impl ParseFromValue for u64 {
+    fn parse_from_value(&mut self, value: Value) {
+        if let Some(v) = value.as_u64() {
+            *self = v;
+        } else {
+            log::error!("Setting expected a u64, but received {:?}", value);
+        }
+    }
+}
+
+impl ParseFromValue for i64 {
+    fn parse_from_value(&mut self, value: Value) {
+        if let Some(v) = value.as_i64() {
+            *self = v;
+        } else {
+            log::error!("Setting expected an i64, but received {:?}", value);
+        }
+    }
+}
+
+impl ParseFromValue for bool {
+    fn parse_from_value(&mut self, value: Value) {
+        if let Some(v) = value.as_bool() {
+            *self = v;
+        } else if let Some(v) = value.as_u64() {
+            *self = v != 0;
+        } else {
+            log::error!("Setting expected a bool or 0/1, but received {:?}", value);
+        }
+    }
+}
+
+impl ParseFromValue for String {
+    fn parse_from_value(&mut self, value: Value) {
+        if let Some(v) = value.as_str() {
+            *self = v.to_string();
+        } else {
+            log::error!("Setting expected a string, but received {:?}", value);
+        }
+    }
+}
+
+/// Declares a settings struct and implements [`SettingGroup`] for it.
+///
+/// Every field is synced with `g:rv_{prefix}_{field}`, the field type must
+/// implement [`ParseFromValue`] and `Into<Value>`.
+macro_rules! setting_group {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident($prefix:literal) {
+            $($field:ident: $ty:ty = $default:expr,)*
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Clone, Debug)]
+        $vis struct $name {
+            $(pub $field: $ty,)*
+        }
+
+        impl Default for $name {
+            fn default() -> Self {
+                $name {
+                    $($field: $default,)*
+                }
+            }
+        }
+
+        impl $crate::settings::SettingGroup for $name {
+            fn register(&self) {
+                $crate::settings::SETTINGS.set(self);
+                $({
+                    fn update(value: nvim::Value) {
+                        let mut group = $crate::settings::SETTINGS.get::<$name>();
+                        $crate::settings::ParseFromValue::parse_from_value(
+                            &mut group.$field,
+                            value,
+                        );
+                        $crate::settings::SETTINGS.set(&group);
+                    }
+                    fn read() -> nvim::Value {
+                        $crate::settings::SETTINGS.get::<$name>().$field.into()
+                    }
+                    let name = if $prefix.is_empty() {
+                        stringify!($field).to_string()
+                    } else {
+                        format!("{}_{}", $prefix, stringify!($field))
+                    };
+                    $crate::settings::SETTINGS.set_setting_handlers(&name, update, read);
+                })*
+            }
+        }
+    };
+}
+
+pub(crate) use setting_group;
+
 // Function types to handle settings updates
 type UpdateHandlerFunc = fn(Value);
 type ReaderFunc = fn() -> Value;
@@ -77,14 +220,19 @@ impl Settings {
         (*value).clone()
     }
 
-    pub async fn read_initial_values(&self, nvim: &Neovim<TxWrapper>) {
+    /// Returns names of settings set by user.
+    pub async fn read_initial_values(&self, nvim: &Neovim<TxWrapper>) -> Vec<String> {
         let keys: Vec<String> = self.listeners.read().keys().cloned().collect();
 
+        let mut read = Vec::new();
         for name in keys {
-            let variable_name = format!("neovide_{}", name);
-            match nvim.get_var(&variable_name).await {
+            let variable_name = format!("{}{}", VARIABLE_PREFIX, name);
+            match get_var(nvim, &name).await {
                 Ok(value) => {
+                    // legacy value is moved to `g:rv_{name}` watched for changes.
+                    nvim.set_var(&variable_name, value.clone()).await.ok();
                     self.listeners.read().get(&name).unwrap()(value);
+                    read.push(name);
                 }
                 Err(error) => {
                     trace!("Initial value load failed for {}: {}", name, error);
@@ -93,6 +241,7 @@ impl Settings {
                 }
             }
         }
+        read
     }
 
     pub async fn setup_changed_listeners(&self, nvim: &Neovim<TxWrapper>) {
@@ -102,12 +251,12 @@ impl Settings {
             let vimscript = format!(
                 concat!(
                     "exe \"",
-                    "fun! NeovideNotify{0}Changed(d, k, z)\n",
-                    "call rpcnotify(1, 'setting_changed', '{0}', g:neovide_{0})\n",
+                    "fun! RvNotify{0}Changed(d, k, z)\n",
+                    "call rpcnotify(1, 'setting_changed', '{0}', g:{1}{0})\n",
                     "endf\n",
-                    "call dictwatcheradd(g:, 'neovide_{0}', 'NeovideNotify{0}Changed')\"",
+                    "call dictwatcheradd(g:, '{1}{0}', 'RvNotify{0}Changed')\"",
                 ),
-                name
+                name, VARIABLE_PREFIX
             );
             nvim.command(&vimscript)
                 .await
@@ -225,6 +374,27 @@ mod tests {
         assert_eq!(v2, r2);
     }
 
+    #[test]
+    fn test_parse_from_value() {
+        let mut float = 0f32;
+        float.parse_from_value(Value::from(0.5f64));
+        assert_eq!(float, 0.5);
+        float.parse_from_value(Value::from(2i64));
+        assert_eq!(float, 2.);
+        float.parse_from_value(Value::from("invalid"));
+        assert_eq!(float, 2.);
+
+        let mut boolean = false;
+        boolean.parse_from_value(Value::from(true));
+        assert!(boolean);
+        boolean.parse_from_value(Value::from(0u64));
+        assert!(!boolean);
+
+        let mut string = String::new();
+        string.parse_from_value(Value::from("railgun"));
+        assert_eq!(string, "railgun");
+    }
+
     #[tokio::test]
     async fn test_read_initial_values() {
         let settings = Settings::new();
@@ -234,8 +404,8 @@ mod tests {
         let v1: String = "foo".to_string();
         let v2: String = "bar".to_string();
         let v3: String = "baz".to_string();
-        let v4: String = format!("neovide_{}", v1);
-        let v5: String = format!("neovide_{}", v2);
+        let v4: String = format!("{}{}", VARIABLE_PREFIX, v1);
+        let v5: String = format!("{}{}", VARIABLE_PREFIX, v2);
 
         //create_nvim_command tries to read from CmdLineSettings.neovim_args
         //TODO: this sets a static variable. Can this have side effects on other tests?
@@ -1,6 +1,16 @@
 use crate::color::{Color, Colors};
 
-#[derive(new, Copy, Clone, Debug, PartialEq)]
+/// Styles of underline, drawn in the special color.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Underline {
+    Single,
+    Double,
+    Curl,
+    Dotted,
+    Dashed,
+}
+
+#[derive(new, Clone, Debug, PartialEq)]
 pub struct Style {
     pub colors: Colors,
     #[new(default)]
@@ -16,6 +26,18 @@ pub struct Style {
     #[new(default)]
     pub undercurl: bool,
     #[new(default)]
+    pub underdouble: bool,
+    #[new(default)]
+    pub underdotted: bool,
+    #[new(default)]
+    pub underdashed: bool,
+    #[new(default)]
+    pub altfont: bool,
+    #[new(default)]
+    pub nocombine: bool,
+    #[new(default)]
+    pub url: Option<String>,
+    #[new(default)]
     pub blend: u8,
 }
 
@@ -40,6 +62,23 @@ impl Style {
         }
     }
 
+    /// Underline drawn, nvim sets only one of them.
+    pub fn underline(&self) -> Option<Underline> {
+        if self.undercurl {
+            Some(Underline::Curl)
+        } else if self.underdouble {
+            Some(Underline::Double)
+        } else if self.underdashed {
+            Some(Underline::Dashed)
+        } else if self.underdotted {
+            Some(Underline::Dotted)
+        } else if self.underline {
+            Some(Underline::Single)
+        } else {
+            None
+        }
+    }
+
     pub fn special(&self, default_colors: &Colors) -> Color {
         self.colors
             .special
@@ -114,6 +153,23 @@ mod tests {
         assert_eq!(style.background(), None);
     }
 
+    #[test]
+    fn test_underline() {
+        let mut style = Style::new(COLORS);
+
+        assert_eq!(style.underline(), None);
+        style.underline = true;
+        assert_eq!(style.underline(), Some(Underline::Single));
+        style.underdotted = true;
+        assert_eq!(style.underline(), Some(Underline::Dotted));
+        style.underdashed = true;
+        assert_eq!(style.underline(), Some(Underline::Dashed));
+        style.underdouble = true;
+        assert_eq!(style.underline(), Some(Underline::Double));
+        style.undercurl = true;
+        assert_eq!(style.underline(), Some(Underline::Curl));
+    }
+
     #[test]
     fn test_special() {
         let mut style = Style::new(COLORS);
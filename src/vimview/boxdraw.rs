@@ -0,0 +1,476 @@
+//! Box drawing, block elements, braille and powerline separators drawn with
+//! cairo to fill the cell exactly, fonts leave gaps with linespace.
+
+use std::f64::consts::PI;
+
+const NONE: u8 = 0;
+const LIGHT: u8 = 1;
+const HEAVY: u8 = 2;
+const DOUBLE: u8 = 3;
+
+// (left, right, up, down) of U+2500 - U+257F, dashes, arcs and diagonals
+// are drawn otherwise.
+#[rustfmt::skip]
+const LINES: [[u8; 4]; 128] = [
+    // ─ ━ │ ┃ ┄ ┅ ┆ ┇
+    [1, 1, 0, 0], [2, 2, 0, 0], [0, 0, 1, 1], [0, 0, 2, 2],
+    [0, 0, 0, 0], [0, 0, 0, 0], [0, 0, 0, 0], [0, 0, 0, 0],
+    // ┈ ┉ ┊ ┋ ┌ ┍ ┎ ┏
+    [0, 0, 0, 0], [0, 0, 0, 0], [0, 0, 0, 0], [0, 0, 0, 0],
+    [0, 1, 0, 1], [0, 2, 0, 1], [0, 1, 0, 2], [0, 2, 0, 2],
+    // ┐ ┑ ┒ ┓ └ ┕ ┖ ┗
+    [1, 0, 0, 1], [2, 0, 0, 1], [1, 0, 0, 2], [2, 0, 0, 2],
+    [0, 1, 1, 0], [0, 2, 1, 0], [0, 1, 2, 0], [0, 2, 2, 0],
+    // ┘ ┙ ┚ ┛ ├ ┝ ┞ ┟
+    [1, 0, 1, 0], [2, 0, 1, 0], [1, 0, 2, 0], [2, 0, 2, 0],
+    [0, 1, 1, 1], [0, 2, 1, 1], [0, 1, 2, 1], [0, 1, 1, 2],
+    // ┠ ┡ ┢ ┣ ┤ ┥ ┦ ┧
+    [0, 1, 2, 2], [0, 2, 2, 1], [0, 2, 1, 2], [0, 2, 2, 2],
+    [1, 0, 1, 1], [2, 0, 1, 1], [1, 0, 2, 1], [1, 0, 1, 2],
+    // ┨ ┩ ┪ ┫ ┬ ┭ ┮ ┯
+    [1, 0, 2, 2], [2, 0, 2, 1], [2, 0, 1, 2], [2, 0, 2, 2],
+    [1, 1, 0, 1], [2, 1, 0, 1], [1, 2, 0, 1], [2, 2, 0, 1],
+    // ┰ ┱ ┲ ┳ ┴ ┵ ┶ ┷
+    [1, 1, 0, 2], [2, 1, 0, 2], [1, 2, 0, 2], [2, 2, 0, 2],
+    [1, 1, 1, 0], [2, 1, 1, 0], [1, 2, 1, 0], [2, 2, 1, 0],
+    // ┸ ┹ ┺ ┻ ┼ ┽ ┾ ┿
+    [1, 1, 2, 0], [2, 1, 2, 0], [1, 2, 2, 0], [2, 2, 2, 0],
+    [1, 1, 1, 1], [2, 1, 1, 1], [1, 2, 1, 1], [2, 2, 1, 1],
+    // ╀ ╁ ╂ ╃ ╄ ╅ ╆ ╇
+    [1, 1, 2, 1], [1, 1, 1, 2], [1, 1, 2, 2], [2, 1, 2, 1],
+    [1, 2, 2, 1], [2, 1, 1, 2], [1, 2, 1, 2], [2, 2, 2, 1],
+    // ╈ ╉ ╊ ╋ ╌ ╍ ╎ ╏
+    [2, 2, 1, 2], [2, 1, 2, 2], [1, 2, 2, 2], [2, 2, 2, 2],
+    [0, 0, 0, 0], [0, 0, 0, 0], [0, 0, 0, 0], [0, 0, 0, 0],
+    // ═ ║ ╒ ╓ ╔ ╕ ╖ ╗
+    [3, 3, 0, 0], [0, 0, 3, 3], [0, 3, 0, 1], [0, 1, 0, 3],
+    [0, 3, 0, 3], [3, 0, 0, 1], [1, 0, 0, 3], [3, 0, 0, 3],
+    // ╘ ╙ ╚ ╛ ╜ ╝ ╞ ╟
+    [0, 3, 1, 0], [0, 1, 3, 0], [0, 3, 3, 0], [3, 0, 1, 0],
+    [1, 0, 3, 0], [3, 0, 3, 0], [0, 3, 1, 1], [0, 1, 3, 3],
+    // ╠ ╡ ╢ ╣ ╤ ╥ ╦ ╧
+    [0, 3, 3, 3], [3, 0, 1, 1], [1, 0, 3, 3], [3, 0, 3, 3],
+    [3, 3, 0, 1], [1, 1, 0, 3], [3, 3, 0, 3], [3, 3, 1, 0],
+    // ╨ ╩ ╪ ╫ ╬ ╭ ╮ ╯
+    [1, 1, 3, 0], [3, 3, 3, 0], [3, 3, 1, 1], [1, 1, 3, 3],
+    [3, 3, 3, 3], [0, 0, 0, 0], [0, 0, 0, 0], [0, 0, 0, 0],
+    // ╰ ╱ ╲ ╳ ╴ ╵ ╶ ╷
+    [0, 0, 0, 0], [0, 0, 0, 0], [0, 0, 0, 0], [0, 0, 0, 0],
+    [1, 0, 0, 0], [0, 0, 1, 0], [0, 1, 0, 0], [0, 0, 0, 1],
+    // ╸ ╹ ╺ ╻ ╼ ╽ ╾ ╿
+    [2, 0, 0, 0], [0, 0, 2, 0], [0, 2, 0, 0], [0, 0, 0, 2],
+    [1, 2, 0, 0], [0, 0, 1, 2], [2, 1, 0, 0], [0, 0, 2, 1],
+];
+
+/// Whether `c` is drawn by [`draw`] instead of the font.
+pub fn is_procedural(c: char) -> bool {
+    matches!(c as u32, 0x2500..=0x259f | 0x2800..=0x28ff | 0xe0b0..=0xe0bf)
+}
+
+/// Draw `c` with the current source of `cr` into the cell at `x`, `y`
+/// sized `width` x `height`, returns false if `c` is not procedural.
+pub fn draw(cr: &cairo::Context, c: char, x: f64, y: f64, width: f64, height: f64) -> bool {
+    let cell = Cell {
+        x,
+        y,
+        width,
+        height,
+    };
+    let code = c as u32;
+    cr.save().unwrap();
+    cr.set_antialias(cairo::Antialias::None);
+    let drawn = match code {
+        0x2500..=0x257f => cell.draw_box(cr, code),
+        0x2580..=0x259f => cell.draw_block(cr, code),
+        0x2800..=0x28ff => cell.draw_braille(cr, (code - 0x2800) as u8),
+        0xe0b0..=0xe0bf => cell.draw_powerline(cr, code),
+        _ => false,
+    };
+    cr.restore().unwrap();
+    drawn
+}
+
+struct Cell {
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+}
+
+impl Cell {
+    fn light(&self) -> f64 {
+        (self.width / 10.).round().max(1.)
+    }
+
+    fn thickness(&self, weight: u8) -> f64 {
+        match weight {
+            HEAVY => self.light() * 2.,
+            NONE => 0.,
+            _ => self.light(),
+        }
+    }
+
+    // center of a line {thickness} thick, on whole pixels.
+    fn center(&self, thickness: f64) -> (f64, f64) {
+        (
+            self.x + ((self.width - thickness) / 2.).floor() + thickness / 2.,
+            self.y + ((self.height - thickness) / 2.).floor() + thickness / 2.,
+        )
+    }
+
+    fn fill(&self, cr: &cairo::Context, x0: f64, y0: f64, x1: f64, y1: f64) {
+        cr.rectangle(x0.min(x1), y0.min(y1), (x1 - x0).abs(), (y1 - y0).abs());
+        cr.fill().unwrap();
+    }
+
+    fn draw_box(&self, cr: &cairo::Context, code: u32) -> bool {
+        match code {
+            // dashes, horizontal and vertical, light and heavy.
+            0x2504..=0x250b => {
+                let dashes = if code < 0x2508 { 3 } else { 4 };
+                self.draw_dashes(cr, dashes, (code - 0x2504) % 4);
+            }
+            0x254c..=0x254f => self.draw_dashes(cr, 2, code - 0x254c),
+            0x256d..=0x2570 => self.draw_arc(cr, code),
+            0x2571..=0x2573 => {
+                cr.set_antialias(cairo::Antialias::Default);
+                cr.set_line_width(self.light());
+                if code != 0x2572 {
+                    cr.move_to(self.x + self.width, self.y);
+                    cr.line_to(self.x, self.y + self.height);
+                }
+                if code != 0x2571 {
+                    cr.move_to(self.x, self.y);
+                    cr.line_to(self.x + self.width, self.y + self.height);
+                }
+                cr.stroke().unwrap();
+            }
+            _ => {
+                let [left, right, up, down] = LINES[(code - 0x2500) as usize];
+                self.draw_lines(cr, left, right, up, down);
+            }
+        }
+        true
+    }
+
+    // {kind} is 0 light horizontal, 1 heavy horizontal, 2 light vertical and
+    // 3 heavy vertical.
+    fn draw_dashes(&self, cr: &cairo::Context, dashes: u32, kind: u32) {
+        let thickness = self.thickness(if kind % 2 == 0 { LIGHT } else { HEAVY });
+        let (cx, cy) = self.center(thickness);
+        let (half, dashes) = (thickness / 2., dashes as f64);
+        if kind < 2 {
+            let step = self.width / dashes;
+            let gap = (step / 4.).round().max(1.);
+            let mut x = self.x;
+            while x < self.x + self.width - 0.5 {
+                self.fill(cr, x, cy - half, x + step - gap, cy + half);
+                x += step;
+            }
+        } else {
+            let step = self.height / dashes;
+            let gap = (step / 4.).round().max(1.);
+            let mut y = self.y;
+            while y < self.y + self.height - 0.5 {
+                self.fill(cr, cx - half, y, cx + half, y + step - gap);
+                y += step;
+            }
+        }
+    }
+
+    fn draw_arc(&self, cr: &cairo::Context, code: u32) {
+        let thickness = self.light();
+        let (cx, cy) = self.center(thickness);
+        let radius = (self.width / 2.).min(self.height / 2.);
+        let (right, bottom) = (self.x + self.width, self.y + self.height);
+        cr.set_antialias(cairo::Antialias::Default);
+        cr.set_line_width(thickness);
+        match code {
+            // ╭
+            0x256d => {
+                cr.move_to(right, cy);
+                cr.arc_negative(cx + radius, cy + radius, radius, PI * 1.5, PI);
+                cr.line_to(cx, bottom);
+            }
+            // ╮
+            0x256e => {
+                cr.move_to(self.x, cy);
+                cr.arc(cx - radius, cy + radius, radius, PI * 1.5, 0.);
+                cr.line_to(cx, bottom);
+            }
+            // ╯
+            0x256f => {
+                cr.move_to(self.x, cy);
+                cr.arc_negative(cx - radius, cy - radius, radius, PI * 0.5, 0.);
+                cr.line_to(cx, self.y);
+            }
+            // ╰
+            _ => {
+                cr.move_to(right, cy);
+                cr.arc(cx + radius, cy - radius, radius, PI * 0.5, PI);
+                cr.line_to(cx, self.y);
+            }
+        }
+        cr.stroke().unwrap();
+    }
+
+    fn draw_lines(&self, cr: &cairo::Context, left: u8, right: u8, up: u8, down: u8) {
+        let light = self.light();
+        // offset of the two lines of double from center.
+        let offset = light;
+        let horizontal = left.max(right);
+        let vertical = up.max(down);
+        let (cx, cy) = self.center(light);
+        let (right_edge, bottom_edge) = (self.x + self.width, self.y + self.height);
+
+        // past the center, reaching the far line of double across.
+        let reach = |across: u8| -> f64 {
+            if across == DOUBLE {
+                offset + light / 2.
+            } else {
+                self.thickness(across) / 2.
+            }
+        };
+        // end of a line of double, which meets lines of double across at
+        // corners.
+        let double_end = |center: f64, sign: f64, across: u8, side: u8| -> f64 {
+            match (across == DOUBLE, side == DOUBLE) {
+                (false, _) => center - sign * reach(across),
+                (true, true) => center + sign * (offset - light / 2.),
+                (true, false) => center - sign * (offset + light / 2.),
+            }
+        };
+
+        for (weight, sign, edge) in [(left, -1., self.x), (right, 1., right_edge)] {
+            match weight {
+                NONE => {}
+                DOUBLE => {
+                    for (y, side) in [(cy - offset, up), (cy + offset, down)] {
+                        let end = double_end(cx, sign, vertical, side);
+                        self.fill(cr, edge, y - light / 2., end, y + light / 2.);
+                    }
+                }
+                _ => {
+                    let half = self.thickness(weight) / 2.;
+                    let end = cx - sign * reach(vertical).max(half);
+                    self.fill(cr, edge, cy - half, end, cy + half);
+                }
+            }
+        }
+        for (weight, sign, edge) in [(up, -1., self.y), (down, 1., bottom_edge)] {
+            match weight {
+                NONE => {}
+                DOUBLE => {
+                    for (x, side) in [(cx - offset, left), (cx + offset, right)] {
+                        let end = double_end(cy, sign, horizontal, side);
+                        self.fill(cr, x - light / 2., edge, x + light / 2., end);
+                    }
+                }
+                _ => {
+                    let half = self.thickness(weight) / 2.;
+                    let end = cy - sign * reach(horizontal).max(half);
+                    self.fill(cr, cx - half, edge, cx + half, end);
+                }
+            }
+        }
+    }
+
+    fn draw_block(&self, cr: &cairo::Context, code: u32) -> bool {
+        let (x, y, w, h) = (self.x, self.y, self.width, self.height);
+        let eighth = |n: u32, size: f64| (size * n as f64 / 8.).round();
+        match code {
+            // ▀
+            0x2580 => self.fill(cr, x, y, x + w, y + eighth(4, h)),
+            // ▁ to █, lower eighths.
+            0x2581..=0x2588 => self.fill(cr, x, y + h - eighth(code - 0x2580, h), x + w, y + h),
+            // ▉ to ▏, left eighths.
+            0x2589..=0x258f => self.fill(cr, x, y, x + eighth(0x2590 - code, w), y + h),
+            // ▐
+            0x2590 => self.fill(cr, x + eighth(4, w), y, x + w, y + h),
+            // ░ ▒ ▓
+            0x2591..=0x2593 => {
+                cr.push_group();
+                self.fill(cr, x, y, x + w, y + h);
+                cr.pop_group_to_source().unwrap();
+                cr.paint_with_alpha((code - 0x2590) as f64 / 4.).unwrap();
+            }
+            // ▔
+            0x2594 => self.fill(cr, x, y, x + w, y + eighth(1, h)),
+            // ▕
+            0x2595 => self.fill(cr, x + w - eighth(1, w), y, x + w, y + h),
+            // quadrants ▖ to ▟.
+            _ => {
+                // bits of upper left, upper right, lower left and lower
+                // right, from the lowest.
+                const QUADRANTS: [u8; 10] = [
+                    0b0100, 0b1000, 0b0001, 0b1101, 0b1001, 0b0111, 0b1011, 0b0010, 0b0110, 0b1110,
+                ];
+                let quadrants = QUADRANTS[(code - 0x2596) as usize];
+                let (mx, my) = (x + eighth(4, w), y + eighth(4, h));
+                if quadrants & 0b0001 != 0 {
+                    self.fill(cr, x, y, mx, my);
+                }
+                if quadrants & 0b0010 != 0 {
+                    self.fill(cr, mx, y, x + w, my);
+                }
+                if quadrants & 0b0100 != 0 {
+                    self.fill(cr, x, my, mx, y + h);
+                }
+                if quadrants & 0b1000 != 0 {
+                    self.fill(cr, mx, my, x + w, y + h);
+                }
+            }
+        }
+        true
+    }
+
+    fn draw_braille(&self, cr: &cairo::Context, dots: u8) -> bool {
+        // (column, row) of dots 1 to 8.
+        const DOTS: [(f64, f64); 8] = [
+            (0., 0.),
+            (0., 1.),
+            (0., 2.),
+            (1., 0.),
+            (1., 1.),
+            (1., 2.),
+            (0., 3.),
+            (1., 3.),
+        ];
+        let (column_width, row_height) = (self.width / 2., self.height / 4.);
+        let size = (column_width.min(row_height) / 2.).round().max(1.);
+        for (bit, (column, row)) in DOTS.iter().enumerate() {
+            if dots & (1 << bit) == 0 {
+                continue;
+            }
+            let x = self.x + (column * column_width + (column_width - size) / 2.).round();
+            let y = self.y + (row * row_height + (row_height - size) / 2.).round();
+            self.fill(cr, x, y, x + size, y + size);
+        }
+        true
+    }
+
+    fn draw_powerline(&self, cr: &cairo::Context, code: u32) -> bool {
+        let (x, y, w, h) = (self.x, self.y, self.width, self.height);
+        let (right, bottom) = (x + w, y + h);
+        cr.set_antialias(cairo::Antialias::Default);
+        cr.set_line_width(self.light());
+        let solid = code % 2 == 0;
+        match code {
+            //
+            0xe0b9 | 0xe0bf => {
+                cr.move_to(x, y);
+                cr.line_to(right, bottom);
+            }
+            //
+            0xe0bb | 0xe0bd => {
+                cr.move_to(right, y);
+                cr.line_to(x, bottom);
+            }
+            //
+            0xe0b0 | 0xe0b1 => {
+                cr.move_to(x, y);
+                cr.line_to(right, y + h / 2.);
+                cr.line_to(x, bottom);
+            }
+            //
+            0xe0b2 | 0xe0b3 => {
+                cr.move_to(right, y);
+                cr.line_to(x, y + h / 2.);
+                cr.line_to(right, bottom);
+            }
+            //
+            0xe0b4 | 0xe0b5 => {
+                cr.move_to(x, y);
+                cr.save().unwrap();
+                cr.translate(x, y + h / 2.);
+                cr.scale(w, h / 2.);
+                cr.arc(0., 0., 1., -PI / 2., PI / 2.);
+                cr.restore().unwrap();
+            }
+            //
+            0xe0b6 | 0xe0b7 => {
+                cr.move_to(right, y);
+                cr.save().unwrap();
+                cr.translate(right, y + h / 2.);
+                cr.scale(w, h / 2.);
+                cr.arc_negative(0., 0., 1., -PI / 2., PI / 2.);
+                cr.restore().unwrap();
+            }
+            //
+            0xe0b8 => {
+                cr.move_to(x, y);
+                cr.line_to(right, bottom);
+                cr.line_to(x, bottom);
+            }
+            //
+            0xe0ba => {
+                cr.move_to(right, y);
+                cr.line_to(x, bottom);
+                cr.line_to(right, bottom);
+            }
+            //
+            0xe0bc => {
+                cr.move_to(x, y);
+                cr.line_to(right, y);
+                cr.line_to(x, bottom);
+            }
+            //
+            _ => {
+                cr.move_to(x, y);
+                cr.line_to(right, y);
+                cr.line_to(right, bottom);
+            }
+        }
+        if solid {
+            cr.close_path();
+            cr.fill().unwrap();
+        } else {
+            cr.stroke().unwrap();
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_procedural() {
+        assert!(is_procedural('─'));
+        assert!(is_procedural('▟'));
+        assert!(is_procedural('⣿'));
+        assert!(is_procedural('\u{e0b0}'));
+        assert!(is_procedural('\u{e0bf}'));
+        assert!(!is_procedural('\u{24ff}'));
+        assert!(!is_procedural('\u{25a0}'));
+        assert!(!is_procedural('\u{27ff}'));
+        assert!(!is_procedural('\u{2900}'));
+        assert!(!is_procedural('\u{e0c0}'));
+        assert!(!is_procedural('a'));
+    }
+
+    #[test]
+    fn test_lines() {
+        let lines = |c: char| LINES[(c as u32 - 0x2500) as usize];
+        // LIGHT HORIZONTAL
+        assert_eq!(lines('─'), [LIGHT, LIGHT, NONE, NONE]);
+        // HEAVY DOWN AND RIGHT
+        assert_eq!(lines('┏'), [NONE, HEAVY, NONE, HEAVY]);
+        // DOWN LIGHT AND RIGHT UP HEAVY
+        assert_eq!(lines('┡'), [NONE, HEAVY, HEAVY, LIGHT]);
+        // HEAVY VERTICAL AND HORIZONTAL
+        assert_eq!(lines('╋'), [HEAVY, HEAVY, HEAVY, HEAVY]);
+        // DOUBLE DOWN AND RIGHT
+        assert_eq!(lines('╔'), [NONE, DOUBLE, NONE, DOUBLE]);
+        // VERTICAL SINGLE AND HORIZONTAL DOUBLE
+        assert_eq!(lines('╪'), [DOUBLE, DOUBLE, LIGHT, LIGHT]);
+        // LIGHT LEFT
+        assert_eq!(lines('╴'), [LIGHT, NONE, NONE, NONE]);
+        // HEAVY UP AND LIGHT DOWN
+        assert_eq!(lines('╿'), [NONE, NONE, HEAVY, LIGHT]);
+        // dashes and arcs are not drawn from the table.
+        assert_eq!(lines('┄'), [NONE; 4]);
+        assert_eq!(lines('╭'), [NONE; 4]);
+    }
+}
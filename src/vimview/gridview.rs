@@ -1,6 +1,6 @@
 mod imp {
     use core::f32;
-    use std::cell::{Cell, Ref};
+    use std::cell::{Cell, Ref, RefCell};
     use std::rc::Rc;
 
     use glib::translate::{from_glib_none, ToGlibPtr};
@@ -8,21 +8,33 @@ mod imp {
     use gtk::{gdk::prelude::*, graphene::Rect, subclass::prelude::*};
     use parking_lot::RwLock;
 
+    use crate::color::Color;
     use crate::metrics::Metrics;
-    use crate::vimview::textbuf::Lines;
-    use crate::vimview::TextCell;
+    use crate::settings::SETTINGS;
+    use crate::style::Underline;
+    use crate::vimview::{FontSettings, TextCell, TextLine};
 
+    use super::super::boxdraw;
     use super::super::highlights::HighlightDefinitions;
     use super::super::TextBuf;
 
     const PANGO_SCALE: f64 = pango::SCALE as f64;
 
+    // pixels of overlay scrollbar.
+    pub(super) const SCROLLBAR_WIDTH: f64 = 6.;
+    pub(super) const SCROLLBAR_MARGIN: f64 = 2.;
+    const SCROLLBAR_MIN_LENGTH: f64 = 16.;
+
+    // features shaping ligatures, off for the cell under cursor.
+    const LIGATURES_OFF: &str = "liga=0, clig=0, dlig=0, calt=0";
+
     #[derive(Clone, Debug)]
     struct CharAttr<'c> {
-        c: char,
         cell: &'c TextCell,
-        // visible width. how much cell used.
-        viswidth: f64,
+        // column of cell.
+        col: usize,
+        // index of following blank cell the glyph may overflow into.
+        overflow: Option<usize>,
     }
 
     // #[derive(Debug)]
@@ -31,7 +43,35 @@ mod imp {
         width: Cell<u64>,
         height: Cell<u64>,
         is_float: Cell<bool>,
+        // (zindex, order) among siblings, larger drawn above.
+        stacking: Cell<Option<(u64, u64)>>,
         textbuf: Cell<TextBuf>,
+
+        // pixels the scrolling region is drawn away from its final place.
+        scroll_offset: Cell<f64>,
+        // offset when the running scroll animation started.
+        scroll_from: Cell<f64>,
+        // rows {top}..{bottom} under scrolling.
+        scroll_region: Cell<(usize, usize)>,
+        // lines scrolled out of region, with the row they belong to now.
+        scrollback: RefCell<Vec<(isize, TextLine)>>,
+
+        // position and size when the running transition started.
+        transition_from: Cell<(f64, f64, f64, f64)>,
+        // position in parent which the transition ends at.
+        transition_to: Cell<(f64, f64)>,
+        // size measured while transition running, instead of size of textbuf.
+        transition_size: Cell<Option<(f64, f64)>>,
+
+        // (top line, bottom line, line count) of window, no scrollbar if none.
+        viewport: Cell<Option<(f64, f64, f64)>>,
+        // overlay scrollbar fades out when idle.
+        scrollbar_opacity: Cell<f64>,
+
+        // (row, column) of cursor on this grid.
+        cursor: Cell<Option<(usize, usize)>>,
+        // (row, start column, end column) of link under pointer.
+        hover_link: Cell<Option<(usize, usize, usize)>>,
     }
 
     impl std::fmt::Debug for VimGridView {
@@ -57,7 +97,23 @@ mod imp {
                 width: 0.into(),
                 height: 0.into(),
                 is_float: false.into(),
+                stacking: None.into(),
                 textbuf: TextBuf::default().into(),
+
+                scroll_offset: 0f64.into(),
+                scroll_from: 0f64.into(),
+                scroll_region: (0, 0).into(),
+                scrollback: RefCell::new(Vec::new()),
+
+                transition_from: (0., 0., 0., 0.).into(),
+                transition_to: (0., 0.).into(),
+                transition_size: None.into(),
+
+                viewport: None.into(),
+                scrollbar_opacity: 0f64.into(),
+
+                cursor: None.into(),
+                hover_link: None.into(),
             }
         }
     }
@@ -176,8 +232,6 @@ mod imp {
             cr.target()
                 .set_device_scale(scale_factor as f64, scale_factor as f64);
 
-            let mut y = metrics.ascent();
-
             let rows = textbuf.rows();
             log::debug!("text to render:");
             let desc = pctx.font_description();
@@ -186,24 +240,92 @@ mod imp {
             layout.set_font_description(desc.as_ref());
             let textbuf = self.textbuf();
             let lines = textbuf.lines();
+            let offset = self.scroll_offset.get();
+            let (top, bottom) = self.scroll_region.get();
+            let scrolling = offset != 0.;
+            let box_drawing = SETTINGS.get::<FontSettings>().box_drawing;
             for lineno in 0..rows {
-                cr.move_to(0., y);
-                y += metrics.height();
+                if scrolling && top <= lineno && lineno < bottom {
+                    continue;
+                }
                 let line = lines.get(lineno).unwrap();
-                let layoutline = if let Some((layout, layoutline)) = line.cache() {
-                    unsafe {
-                        let layout: *mut pango::ffi::PangoLayout = layout.to_glib_none().0;
-                        (*layoutline.to_glib_none().0).layout = layout;
+                let y = lineno as f64 * metrics.height() + metrics.ascent();
+                self.show_line(&cr, &mut layout, line, lineno, y, &metrics, &hldefs);
+                self.draw_procedural(&cr, line, y, &metrics, &hldefs, box_drawing);
+                self.draw_underlines(&cr, line, y, &metrics, &hldefs);
+            }
+            if scrolling {
+                // region and lines scrolled out of it, clipped to region.
+                cr.save().unwrap();
+                cr.rectangle(
+                    0.,
+                    top as f64 * metrics.height(),
+                    width as f64,
+                    (bottom - top) as f64 * metrics.height(),
+                );
+                cr.clip();
+                for lineno in top..bottom.min(rows) {
+                    let line = lines.get(lineno).unwrap();
+                    let y = lineno as f64 * metrics.height() + metrics.ascent() + offset;
+                    self.show_line(&cr, &mut layout, line, lineno, y, &metrics, &hldefs);
+                    self.draw_procedural(&cr, line, y, &metrics, &hldefs, box_drawing);
+                    self.draw_underlines(&cr, line, y, &metrics, &hldefs);
+                }
+                for (row, line) in self.scrollback.borrow().iter() {
+                    let y = *row as f64 * metrics.height() + metrics.ascent() + offset;
+                    self.show_line(
+                        &cr,
+                        &mut layout,
+                        line,
+                        (*row).max(0) as usize,
+                        y,
+                        &metrics,
+                        &hldefs,
+                    );
+                    self.draw_procedural(&cr, line, y, &metrics, &hldefs, box_drawing);
+                    self.draw_underlines(&cr, line, y, &metrics, &hldefs);
+                }
+                cr.restore().unwrap();
+            }
+            if let Some((row, start, end)) = self.hover_link.get() {
+                let style = lines
+                    .get(row)
+                    .and_then(|line| line.get(start))
+                    .and_then(|cell| cell.hldef)
+                    .and_then(|id| hldefs.get(id))
+                    .or(hldef);
+                if let (Some(style), Some(defaults)) = (style, hldefs.defaults()) {
+                    let color = style.foreground(defaults);
+                    // moves with lines of scrolling region.
+                    let offset = if scrolling && top <= row && row < bottom {
+                        offset
+                    } else {
+                        0.
                     };
-                    pangocairo::update_layout(&cr, &layout);
-                    layoutline
-                } else {
-                    let layoutline = self.layoutline(&mut layout, &lines, lineno, &metrics);
-                    line.set_cache(layout.copy().unwrap(), layoutline.clone());
-                    pangocairo::update_layout(&cr, &layout);
-                    layoutline
-                };
-                pangocairo::show_layout_line(&cr, &layoutline);
+                    let y = row as f64 * metrics.height()
+                        + metrics.ascent()
+                        + metrics.underline_position()
+                        + offset;
+                    cr.set_source_rgba(
+                        color.red() as f64,
+                        color.green() as f64,
+                        color.blue() as f64,
+                        color.alpha() as f64,
+                    );
+                    cr.rectangle(
+                        start as f64 * metrics.width(),
+                        y,
+                        (end - start) as f64 * metrics.width(),
+                        metrics.underline_thickness(),
+                    );
+                    cr.fill().unwrap();
+                }
+            }
+            if let Some(foreground) = hldef
+                .map(|style| &style.colors)
+                .and_then(|colors| colors.foreground)
+            {
+                self.draw_scrollbar(&cr, &foreground);
             }
             let elapsed = instant.elapsed().as_secs_f32() * 1000.;
             log::info!("snapshot used: {:.3}ms", elapsed);
@@ -257,28 +379,338 @@ mod imp {
             self.is_float.replace(is_float);
         }
 
+        pub(super) fn is_float(&self) -> bool {
+            self.is_float.get()
+        }
+
+        pub(super) fn stacking(&self) -> Option<(u64, u64)> {
+            self.stacking.get()
+        }
+
+        pub(super) fn set_stacking(&self, stacking: (u64, u64)) -> bool {
+            self.stacking.replace(Some(stacking)) != Some(stacking)
+        }
+
         pub(super) fn set_metrics(&self, metrics: Rc<Cell<crate::metrics::Metrics>>) {
             self.textbuf().set_metrics(metrics)
         }
 
         pub(super) fn size_required(&self) -> (i32, i32) {
+            let (w, h) = self
+                .transition_size
+                .get()
+                .unwrap_or_else(|| self.content_size());
+            (w.ceil() as i32, h.ceil() as i32)
+        }
+
+        fn content_size(&self) -> (f64, f64) {
             let textbuf = self.textbuf();
             let width = textbuf.cols() as f64;
             let height = textbuf.rows() as f64;
             let metrics = textbuf.metrics().unwrap().get();
-            let w = width * metrics.width();
-            let h = height * metrics.height();
-            (w.ceil() as i32, h.ceil() as i32)
+            (width * metrics.width(), height * metrics.height())
+        }
+
+        pub(super) fn set_transition(&self, from: (f64, f64, f64, f64), to: (f64, f64)) {
+            self.transition_from.set(from);
+            self.transition_to.set(to);
+            self.transition_size.set(Some((from.2, from.3)));
+        }
+
+        /// `progress` of transition, from 0. to 1., returns position in parent.
+        pub(super) fn set_transition_progress(&self, progress: f64) -> (f64, f64) {
+            use crate::animation::lerp;
+            let (x, y, w, h) = self.transition_from.get();
+            let (to_x, to_y) = self.transition_to.get();
+            // follow size of textbuf, it may resize again while moving.
+            let (to_w, to_h) = self.content_size();
+            self.transition_size
+                .set(Some((lerp(w, to_w, progress), lerp(h, to_h, progress))));
+            (lerp(x, to_x, progress), lerp(y, to_y, progress))
+        }
+
+        pub(super) fn clear_transition(&self) {
+            self.transition_size.set(None);
+        }
+
+        pub(super) fn is_transiting(&self) -> bool {
+            self.transition_size.get().is_some()
+        }
+
+        pub(super) fn set_viewport(&self, viewport: Option<(f64, f64, f64)>) -> bool {
+            self.viewport.replace(viewport) != viewport
+        }
+
+        /// Returns previous cursor.
+        pub(super) fn set_cursor(&self, cursor: Option<(usize, usize)>) -> Option<(usize, usize)> {
+            self.cursor.replace(cursor)
+        }
+
+        /// Returns previous link.
+        pub(super) fn set_hover_link(
+            &self,
+            link: Option<(usize, usize, usize)>,
+        ) -> Option<(usize, usize, usize)> {
+            self.hover_link.replace(link)
+        }
+
+        pub(super) fn set_scrollbar_opacity(&self, opacity: f64) {
+            self.scrollbar_opacity.set(opacity);
+        }
+
+        /// Top and length of scrollbar thumb in pixels, none if whole buffer
+        /// is visible.
+        pub(super) fn scrollbar_thumb(&self) -> Option<(f64, f64)> {
+            let (top, bottom, count) = self.viewport.get()?;
+            let visible = bottom - top;
+            if count <= visible || visible <= 0. {
+                return None;
+            }
+            let (_, height) = self.content_size();
+            let length = (visible / count * height)
+                .max(SCROLLBAR_MIN_LENGTH)
+                .min(height);
+            let y = top / (count - visible) * (height - length);
+            Some((y.clamp(0., height - length), length))
+        }
+
+        /// Top line of window with scrollbar thumb at {y}.
+        pub(super) fn scrollbar_line(&self, y: f64) -> Option<f64> {
+            let (top, bottom, count) = self.viewport.get()?;
+            let (_, length) = self.scrollbar_thumb()?;
+            let (_, height) = self.content_size();
+            let progress = (y / (height - length).max(1.)).clamp(0., 1.);
+            Some((progress * (count - (bottom - top))).round())
+        }
+
+        fn draw_scrollbar(&self, cr: &cairo::Context, color: &gtk::gdk::RGBA) {
+            let opacity = self.scrollbar_opacity.get();
+            let (y, length) = match self.scrollbar_thumb() {
+                Some(thumb) if opacity > 0. => thumb,
+                _ => return,
+            };
+            let (width, _) = self.content_size();
+            let x = width - SCROLLBAR_WIDTH - SCROLLBAR_MARGIN;
+            let radius = SCROLLBAR_WIDTH / 2.;
+            cr.save().unwrap();
+            cr.new_sub_path();
+            cr.arc(x + radius, y + radius, radius, std::f64::consts::PI, 0.);
+            cr.arc(
+                x + radius,
+                y + length - radius,
+                radius,
+                0.,
+                std::f64::consts::PI,
+            );
+            cr.close_path();
+            cr.set_source_rgba(
+                color.red() as f64,
+                color.green() as f64,
+                color.blue() as f64,
+                0.5 * opacity,
+            );
+            cr.fill().unwrap();
+            cr.restore().unwrap();
+        }
+
+        pub(super) fn scroll_offset(&self) -> f64 {
+            self.scroll_offset.get()
+        }
+
+        /// Keep `scrolled` lines for drawing, and move the region back to
+        /// where it was before scrolling.
+        pub(super) fn push_scrollback(
+            &self,
+            top: usize,
+            bottom: usize,
+            rows: isize,
+            scrolled: Vec<TextLine>,
+            height: f64,
+        ) {
+            let mut scrollback = self.scrollback.borrow_mut();
+            scrollback.iter_mut().for_each(|(row, _)| *row -= rows);
+            let first = if rows > 0 {
+                top as isize - rows
+            } else {
+                bottom as isize
+            };
+            scrollback.extend(
+                scrolled
+                    .into_iter()
+                    .enumerate()
+                    .map(|(idx, line)| (first + idx as isize, line)),
+            );
+
+            let offset = self.scroll_offset.get() + rows as f64 * height;
+            // drop lines which would never be visible again.
+            let reach = (offset.abs() / height).ceil() as isize;
+            let (top, bottom) = (top as isize, bottom as isize);
+            scrollback.retain(|(row, _)| {
+                (top - reach <= *row && *row < top) || (bottom <= *row && *row < bottom + reach)
+            });
+
+            self.scroll_offset.set(offset);
+            self.scroll_from.set(offset);
+        }
+
+        pub(super) fn set_scroll_region(&self, top: usize, bottom: usize) {
+            self.scroll_region.set((top, bottom));
+        }
+
+        pub(super) fn scroll_region(&self) -> (usize, usize) {
+            self.scroll_region.get()
+        }
+
+        /// `progress` of scroll animation, from 0. to 1.
+        pub(super) fn set_scroll_progress(&self, progress: f64) {
+            self.scroll_offset
+                .set(self.scroll_from.get() * (1. - progress));
+        }
+
+        pub(super) fn clear_scrollback(&self) {
+            self.scroll_offset.set(0.);
+            self.scroll_from.set(0.);
+            self.scrollback.borrow_mut().clear();
+        }
+
+        fn show_line(
+            &self,
+            cr: &cairo::Context,
+            layout: &mut pango::Layout,
+            line: &TextLine,
+            lineno: usize,
+            y: f64,
+            metrics: &Metrics,
+            hldefs: &HighlightDefinitions,
+        ) {
+            cr.move_to(0., y);
+            let layoutline = if let Some((layout, layoutline)) = line.cache() {
+                unsafe {
+                    let layout: *mut pango::ffi::PangoLayout = layout.to_glib_none().0;
+                    (*layoutline.to_glib_none().0).layout = layout;
+                };
+                pangocairo::update_layout(cr, &layout);
+                layoutline
+            } else {
+                let layoutline = self.layoutline(layout, line, lineno, metrics, hldefs);
+                line.set_cache(layout.copy().unwrap(), layoutline.clone());
+                pangocairo::update_layout(cr, layout);
+                layoutline
+            };
+            pangocairo::show_layout_line(cr, &layoutline);
+        }
+
+        /// Draw cells of [`boxdraw`] on line with baseline at `y`.
+        fn draw_procedural(
+            &self,
+            cr: &cairo::Context,
+            line: &TextLine,
+            y: f64,
+            metrics: &Metrics,
+            hldefs: &HighlightDefinitions,
+            enabled: bool,
+        ) {
+            if !enabled || !line.has_procedural() {
+                return;
+            }
+            let defaults = match hldefs.defaults() {
+                Some(defaults) => defaults,
+                None => return,
+            };
+            let top = y - metrics.ascent();
+            for (col, cell) in line.iter().enumerate() {
+                if !cell.is_procedural() {
+                    continue;
+                }
+                let style = cell
+                    .hldef
+                    .and_then(|id| hldefs.get(id))
+                    .or_else(|| hldefs.get(HighlightDefinitions::DEFAULT));
+                let color = match style {
+                    Some(style) => style.foreground(defaults),
+                    None => continue,
+                };
+                cr.set_source_rgba(
+                    color.red() as f64,
+                    color.green() as f64,
+                    color.blue() as f64,
+                    color.alpha() as f64,
+                );
+                let c = cell.text.chars().next().unwrap();
+                let x = col as f64 * metrics.width();
+                boxdraw::draw(cr, c, x, top, cell.width(metrics), metrics.height());
+            }
+        }
+
+        /// Draw underlines of cells on line with baseline at `y`, in the
+        /// special color at position and thickness of the font.
+        fn draw_underlines(
+            &self,
+            cr: &cairo::Context,
+            line: &TextLine,
+            y: f64,
+            metrics: &Metrics,
+            hldefs: &HighlightDefinitions,
+        ) {
+            let defaults = match hldefs.defaults() {
+                Some(defaults) => defaults,
+                None => return,
+            };
+            // consecutive cells of same underline are drawn together,
+            // patterns continue across cells.
+            let mut segments: Vec<(usize, usize, Underline, Color)> = Vec::new();
+            for (col, cell) in line.iter().enumerate() {
+                let style = match cell.hldef.and_then(|id| hldefs.get(id)) {
+                    Some(style) => style,
+                    None => continue,
+                };
+                let underline = match style.underline() {
+                    Some(underline) => underline,
+                    None => continue,
+                };
+                let color = style.special(defaults);
+                match segments.last_mut() {
+                    Some((_, end, u, c)) if *end == col && *u == underline && *c == color => {
+                        *end = col + 1;
+                    }
+                    _ => segments.push((col, col + 1, underline, color)),
+                }
+            }
+            if segments.is_empty() {
+                return;
+            }
+            for (start, end, underline, color) in segments {
+                cr.set_source_rgba(
+                    color.red() as f64,
+                    color.green() as f64,
+                    color.blue() as f64,
+                    color.alpha() as f64,
+                );
+                super::draw_underline(
+                    cr,
+                    underline,
+                    start as f64 * metrics.width(),
+                    end as f64 * metrics.width(),
+                    y,
+                    metrics,
+                );
+            }
         }
 
         fn layoutline(
             &self,
             layout: &mut pango::Layout,
-            lines: &Lines,
+            line: &TextLine,
             lineno: usize,
             metrics: &Metrics,
+            hldefs: &HighlightDefinitions,
         ) -> pango::LayoutLine {
-            let line = lines.get(lineno).unwrap();
+            let settings = SETTINGS.get::<FontSettings>();
+            let background = |cell: &TextCell| {
+                cell.hldef
+                    .and_then(|id| hldefs.get(id))
+                    .and_then(|style| style.background())
+            };
             let cols = line.len();
             let mut text = String::new();
             let mut chars: Vec<Option<CharAttr>> = vec![None; cols * 2];
@@ -288,43 +720,29 @@ mod imp {
                 if cell.start_index == cell.end_index {
                     continue;
                 }
-                if chars.len() <= text.len() {
-                    chars.resize(chars.len() * 2, None);
-                }
-                let mut chars_ = cell.text.chars();
-                let mut index = text.len();
-
-                if let Some(c) = chars_.next() {
-                    chars[index] = {
-                        CharAttr {
-                            c,
-                            cell,
-                            viswidth: if cell.double_width {
-                                2.
-                            } else if pango::is_zero_width(c) {
-                                0.
-                            } else {
-                                1.
-                            },
-                        }
-                    }
-                    .into();
-                    index += c.to_string().bytes().len();
-                } else {
-                    continue;
+                let index = text.len();
+                // long for emoji of zwj sequences.
+                if chars.len() < index + cell.text.len() {
+                    chars.resize((index + cell.text.len()) * 2, None);
                 }
-
-                // normally only one char at here
-                for c in chars_ {
-                    chars[index] = {
-                        CharAttr {
-                            c,
-                            cell,
-                            viswidth: 0.,
-                        }
-                    }
-                    .into();
-                    index += c.to_string().bytes().len();
+                let overflow = line
+                    .get(col + 1)
+                    .filter(|next| {
+                        settings.icon_overflow
+                            && !cell.double_width
+                            && !(settings.box_drawing && cell.is_procedural())
+                            && next.text == " "
+                            && background(next) == background(cell)
+                    })
+                    .map(|_| index + cell.text.len());
+                // whole text of cell is one cluster.
+                let charattr = CharAttr {
+                    cell,
+                    col,
+                    overflow,
+                };
+                for (offset, _) in cell.text.char_indices() {
+                    chars[index + offset] = Some(charattr.clone());
                 }
                 text.push_str(&cell.text);
                 cell.attrs
@@ -332,6 +750,35 @@ mod imp {
                     .into_iter()
                     .for_each(|attr| attrs.change(attr));
             }
+            let family = layout
+                .font_description()
+                .and_then(|desc| desc.family())
+                .map(|family| {
+                    family
+                        .split(',')
+                        .next()
+                        .unwrap_or_default()
+                        .trim()
+                        .to_string()
+                })
+                .unwrap_or_default();
+            if let Some(features) = settings.features.get(&family) {
+                let mut attr = pango::AttrFontFeatures::new(&features);
+                attr.set_start_index(0);
+                attr.set_end_index(pango::ATTR_INDEX_TO_TEXT_END);
+                attrs.insert_before(attr);
+            }
+            if let Some(cell) = self
+                .cursor
+                .get()
+                .filter(|(row, _)| settings.break_ligatures && *row == lineno)
+                .and_then(|(_, col)| line.get(col))
+            {
+                let mut attr = pango::AttrFontFeatures::new(LIGATURES_OFF);
+                attr.set_start_index(cell.start_index as _);
+                attr.set_end_index(cell.end_index as _);
+                attrs.insert(attr);
+            }
             layout.set_text(&text);
             layout.set_attributes(Some(&attrs));
             let unknown_glyphs = layout.unknown_glyphs_count();
@@ -364,88 +811,111 @@ mod imp {
                 log::debug!("Scale line height failed.");
             }
 
-
-            let layoutline: pango::LayoutLine = unsafe { self.align(layout, &chars, &metrics) };
+            let layoutline: pango::LayoutLine =
+                unsafe { self.align(layout, &chars, cols, &metrics) };
             layoutline
         }
 
+        /// Fit clusters of glyphs to cells, each cell is one cluster of
+        /// its whole text, like emoji of zwj sequences, flags or letters
+        /// with combining marks, shaped into one or more glyphs.
         unsafe fn align(
             &self,
             layout: &mut pango::Layout,
             chars: &Vec<Option<CharAttr>>,
+            cols: usize,
             metrics: &Metrics,
         ) -> pango::LayoutLine {
             // let _baseline = pango::ffi::pango_layout_get_baseline(layout.to_glib_none().0);
             let layoutline = pango::ffi::pango_layout_get_line(layout.to_glib_none().0, 0);
             let mut runs = (*layoutline).runs;
-            loop {
+            // glyphs by cell, in visual order, a cluster may be split to runs
+            // of different fonts.
+            let mut clusters: Vec<(&CharAttr, Vec<*mut pango::ffi::PangoGlyphInfo>)> = Vec::new();
+            while !runs.is_null() {
                 let run = (*runs).data as *mut pango::ffi::PangoLayoutRun;
                 let item = (*run).item;
-                let font = (*item).analysis.font;
                 let glyph_string = (*run).glyphs;
                 let num_glyphs = (*glyph_string).num_glyphs as usize;
                 let log_clusters =
                     { std::slice::from_raw_parts((*glyph_string).log_clusters, num_glyphs) };
                 let glyphs = { std::slice::from_raw_parts_mut((*glyph_string).glyphs, num_glyphs) };
-                let ink_rect = std::ptr::null_mut();
-                let mut logical_rect = pango::ffi::PangoRectangle {
-                    x: 0,
-                    y: 0,
-                    width: 0,
-                    height: 0,
-                };
-
-                pango::ffi::pango_glyph_string_extents(
-                    glyph_string,
-                    font,
-                    ink_rect,
-                    &mut logical_rect,
-                );
                 log::trace!("{} glyphs item.offset {}", num_glyphs, (*item).offset);
                 log::trace!("log_clusters{:?}", log_clusters);
                 for (glyph, log_cluster) in glyphs.iter_mut().zip(log_clusters) {
                     let index = ((*item).offset + log_cluster) as usize;
-                    let isfirst = index == 0;
                     let charattr = chars.get(index).unwrap().as_ref().unwrap_or_else(|| {
                         // lazy, format is expensive.
                         panic!("index {} out of range, {:?}", index, &chars)
                     });
-                    if charattr.viswidth == 0. {
-                        log::debug!("Skipping zerowidth: {}", charattr.cell.text);
-                        continue;
-                    }
-                    let width = metrics.charwidth() * charattr.viswidth * PANGO_SCALE;
-                    let width = width.ceil() as i32;
-                    let geometry = &mut glyph.geometry;
-                    // log::info!("{} char-cell {:?}", index, charattr.cell);
-                    if geometry.width > 0 && geometry.width != width {
-                        let x_offset = if isfirst {
-                            geometry.x_offset
-                        } else {
-                            geometry.x_offset - (geometry.width - width) / 2
-                        };
-                        let y_offset = geometry.y_offset
-                            - (logical_rect.height / pango::SCALE - metrics.height() as i32) / 2;
-                        // 啊啊啊啊啊啊啊啊啊啊啊啊啊啊啊啊啊啊啊啊啊啊啊啊啊啊啊啊啊啊啊啊
-                        log::debug!(
-                            "adjusting ({})  width {}->{}  x-offset {}->{} y-offset {} -> {} is-start-char {}",
-                            charattr.c,
-                            geometry.width,
-                            width,
-                            geometry.x_offset,
-                            x_offset,
-                            geometry.y_offset,
-                            y_offset,
-                            isfirst
-                        );
-                        geometry.width = width;
-                        geometry.x_offset = x_offset;
-                        // geometry.y_offset = y_offset;
+                    let glyph = glyph as *mut pango::ffi::PangoGlyphInfo;
+                    match clusters.last_mut() {
+                        Some((first, glyphs)) if first.col == charattr.col => glyphs.push(glyph),
+                        _ => clusters.push((charattr, vec![glyph])),
                     }
                 }
                 runs = (*runs).next;
-                if runs.is_null() {
-                    break;
+            }
+            let cellwidth = (metrics.charwidth() * PANGO_SCALE).ceil() as i32;
+            // left and width of each cluster on the grid, by columns.
+            let mut order: Vec<usize> = (0..clusters.len()).collect();
+            order.sort_by_key(|i| clusters[*i].0.col);
+            let mut places = vec![(0, 0, true); clusters.len()];
+            // blank cell overflowed by previous glyph.
+            let mut overflowed = false;
+            for (n, i) in order.iter().enumerate() {
+                let (charattr, glyphs) = &clusters[*i];
+                let next = order.get(n + 1).map(|next| clusters[*next].0);
+                // cells of ligatures without glyphs belong to previous one.
+                let cells = next.map(|next| next.col).unwrap_or(cols) - charattr.col;
+                let mut x = cellwidth * charattr.col as i32;
+                let mut width = cellwidth * cells as i32;
+                let natural: i32 = glyphs.iter().map(|glyph| (**glyph).geometry.width).sum();
+                // the first cell keeps glyph unmoved, not clipped by left edge.
+                let mut centered = charattr.col != 0 || cells > 1;
+                if overflowed {
+                    // space taken by previous glyph.
+                    overflowed = false;
+                    x += cellwidth;
+                    width -= cellwidth;
+                } else if cells == 1
+                    && natural > width
+                    && charattr.overflow.is_some()
+                    && charattr.overflow == next.map(|next| next.cell.start_index)
+                {
+                    // wide glyph takes blank cell after it.
+                    overflowed = true;
+                    width += cellwidth;
+                    centered = natural > width;
+                }
+                places[*i] = (x, width, centered);
+            }
+
+            // glyphs are drawn in visual order, right to left text like
+            // arabic is reordered by pango, move clusters back to cells.
+            let mut pen = 0;
+            for ((charattr, glyphs), (x, width, centered)) in clusters.iter().zip(places) {
+                let natural: i32 = glyphs.iter().map(|glyph| (**glyph).geometry.width).sum();
+                pen += width;
+                if natural == width && pen - width == x {
+                    continue;
+                }
+                let center = if centered { (width - natural) / 2 } else { 0 };
+                let shift = x - (pen - width) + center;
+                // 啊啊啊啊啊啊啊啊啊啊啊啊啊啊啊啊啊啊啊啊啊啊啊啊啊啊啊啊啊啊啊啊
+                log::debug!(
+                    "adjusting ({}) {} glyphs width {}->{} x-offset {}",
+                    charattr.cell.text,
+                    glyphs.len(),
+                    natural,
+                    width,
+                    shift,
+                );
+                for glyph in glyphs.iter() {
+                    (**glyph).geometry.x_offset += shift;
+                }
+                if let Some(last) = glyphs.last() {
+                    (**last).geometry.width += width - natural;
                 }
             }
             from_glib_none(layoutline)
@@ -454,13 +924,82 @@ mod imp {
 }
 
 use std::cell::{Cell, Ref};
+use std::ops::Range;
 use std::rc::Rc;
 
 use glib::subclass::prelude::*;
 use gtk::prelude::*;
 use parking_lot::RwLock;
 
-use super::{HighlightDefinitions, TextBuf};
+use super::{FontSettings, HighlightDefinitions, TextBuf, WindowSettings};
+use crate::animation::ease_out_cubic;
+use crate::metrics::Metrics;
+use crate::scheduler::scheduler;
+use crate::settings::SETTINGS;
+use crate::style::Underline;
+
+/// Draw `underline` with the current source of `cr` from `x0` to `x1`
+/// below baseline at `y`, at position and thickness of the font.
+pub fn draw_underline(
+    cr: &cairo::Context,
+    underline: Underline,
+    x0: f64,
+    x1: f64,
+    y: f64,
+    metrics: &Metrics,
+) {
+    let thickness = metrics.underline_thickness();
+    let top = y + metrics.underline_position();
+    cr.save().unwrap();
+    cr.set_line_width(thickness);
+    match underline {
+        Underline::Single => {
+            cr.rectangle(x0, top, x1 - x0, thickness);
+            cr.fill().unwrap();
+        }
+        Underline::Double => {
+            cr.rectangle(x0, top, x1 - x0, thickness);
+            cr.rectangle(x0, top + thickness * 2., x1 - x0, thickness);
+            cr.fill().unwrap();
+        }
+        Underline::Dotted | Underline::Dashed => {
+            let (dash, gap) = if underline == Underline::Dotted {
+                (thickness, thickness)
+            } else {
+                (thickness * 4., thickness * 2.)
+            };
+            cr.set_dash(&[dash, gap], x0 % (dash + gap));
+            cr.move_to(x0, top + thickness / 2.);
+            cr.line_to(x1, top + thickness / 2.);
+            cr.stroke().unwrap();
+            cr.set_dash(&[], 0.);
+        }
+        Underline::Curl => {
+            // a wave each cell, between top of underline and
+            // two thickness below.
+            let amplitude = thickness;
+            let center = top + thickness / 2. + amplitude;
+            let half = metrics.width() / 2.;
+            cr.move_to(x0, center);
+            let mut x = x0;
+            let mut sign = -1.;
+            while x < x1 - 0.5 {
+                cr.curve_to(
+                    x + half / 3.,
+                    center + sign * amplitude * 4. / 3.,
+                    x + half * 2. / 3.,
+                    center + sign * amplitude * 4. / 3.,
+                    x + half,
+                    center,
+                );
+                x += half;
+                sign = -sign;
+            }
+            cr.stroke().unwrap();
+        }
+    }
+    cr.restore().unwrap();
+}
 
 glib::wrapper! {
     pub struct VimGridView(ObjectSubclass<imp::VimGridView>)
@@ -496,6 +1035,44 @@ impl VimGridView {
         self.imp().set_is_float(is_float);
     }
 
+    pub fn is_float(&self) -> bool {
+        self.imp().is_float()
+    }
+
+    /// Position in parent `gtk::Fixed`.
+    pub fn position(&self) -> Option<(f64, f64)> {
+        self.parent()
+            .and_then(|p| p.downcast::<gtk::Fixed>().ok())
+            .map(|fixed| fixed.child_position(self))
+    }
+
+    /// Restack among sibling grids by `zindex`, then `order` for floats of
+    /// same zindex, grids in front are drawn later.
+    pub fn set_stacking(&self, zindex: u64, order: u64) {
+        if !self.imp().set_stacking((zindex, order)) {
+            return;
+        }
+        let parent = match self.parent() {
+            Some(parent) => parent,
+            None => return,
+        };
+        let stacking = self.imp().stacking();
+        let mut child = parent.first_child();
+        while let Some(sibling) = child {
+            let above = sibling
+                .downcast_ref::<VimGridView>()
+                .filter(|view| *view != *self)
+                .map(|view| view.imp().stacking() > stacking)
+                .unwrap_or(false);
+            if above {
+                self.insert_before(&parent, Some(&sibling));
+                return;
+            }
+            child = sibling.next_sibling();
+        }
+        self.insert_before(&parent, None::<&gtk::Widget>);
+    }
+
     pub fn set_font_description(&self, desc: &pango::FontDescription) {
         self.pango_context().set_font_description(desc);
     }
@@ -509,8 +1086,192 @@ impl VimGridView {
     }
 
     pub fn resize(&self, width: u64, height: u64) {
+        self.stop_scrolling();
         self.imp().set_width(width);
         self.imp().set_height(height);
         self.imp().textbuf().resize(height as _, width as _);
     }
+
+    /// Animate rows {top}..{bottom} from where they were before scrolling
+    /// by `rows`, `scrolled` are the lines which had scrolled out.
+    pub fn scroll(
+        &self,
+        top: usize,
+        bottom: usize,
+        rows: isize,
+        scrolled: Vec<super::TextLine>,
+        duration: u32,
+    ) {
+        let imp = self.imp();
+        if duration == 0 || rows == 0 {
+            self.stop_scrolling();
+            return;
+        }
+        if imp.scroll_offset() != 0. && imp.scroll_region() != (top, bottom) {
+            self.stop_scrolling();
+        }
+        let height = imp.textbuf().metrics().unwrap().get().height();
+        imp.set_scroll_region(top, bottom);
+        imp.push_scrollback(top, bottom, rows, scrolled, height);
+
+        // restart from current offset, scrolling again while animating.
+        self.animate("scroll", duration, |view, progress| {
+            if progress < 1. {
+                view.imp().set_scroll_progress(ease_out_cubic(progress));
+            } else {
+                view.imp().clear_scrollback();
+            }
+            view.queue_draw();
+        });
+    }
+
+    /// Move to {x}x{y} of parent `gtk::Fixed`, easing position and size
+    /// from the current allocation in `duration` milliseconds.
+    pub fn transition(&self, x: f64, y: f64, duration: u32) {
+        let fixed = match self.parent().and_then(|p| p.downcast::<gtk::Fixed>().ok()) {
+            Some(fixed) => fixed,
+            None => return,
+        };
+        let imp = self.imp();
+        let (from_x, from_y) = fixed.child_position(self);
+        let (width, height) = (self.width() as f64, self.height() as f64);
+        if duration == 0 || !self.is_mapped() || width <= 0. || height <= 0. {
+            self.stop_transition();
+            fixed.move_(self, x, y);
+            return;
+        }
+        imp.set_transition((from_x, from_y, width, height), (x, y));
+
+        // restart from current geometry, moving again while animating.
+        let fixed = fixed.downgrade();
+        self.animate("transition", duration, move |view, progress| {
+            let (x, y) = view.imp().set_transition_progress(ease_out_cubic(progress));
+            if let Some(fixed) = fixed.upgrade() {
+                fixed.move_(view, x, y);
+            }
+            if progress >= 1. {
+                view.imp().clear_transition();
+            }
+            view.queue_resize();
+        });
+    }
+
+    pub fn stop_transition(&self) {
+        scheduler().cancel(self.job("transition"));
+        let imp = self.imp();
+        if imp.is_transiting() {
+            // jumps to where the transition ends.
+            let (x, y) = imp.set_transition_progress(1.);
+            if let Some(fixed) = self.parent().and_then(|p| p.downcast::<gtk::Fixed>().ok()) {
+                fixed.move_(self, x, y);
+            }
+        }
+        imp.clear_transition();
+        self.queue_resize();
+    }
+
+    /// Show viewport of window on overlay scrollbar, `None` hides it.
+    pub fn set_viewport(&self, viewport: Option<(f64, f64, f64)>) {
+        if self.imp().set_viewport(viewport) {
+            self.flash_scrollbar();
+        }
+    }
+
+    /// Cursor at (row, column) of this grid, its cell is shaped without
+    /// ligatures if `g:rv_font_break_ligatures` is set.
+    pub fn set_cursor(&self, cursor: Option<(usize, usize)>) {
+        let previous = self.imp().set_cursor(cursor);
+        if previous == cursor || !SETTINGS.get::<FontSettings>().break_ligatures {
+            return;
+        }
+        let textbuf = self.textbuf();
+        let lines = textbuf.lines();
+        previous
+            .iter()
+            .chain(cursor.iter())
+            .filter_map(|(row, _)| lines.get(*row))
+            .for_each(|line| line.clear_cache());
+        self.queue_draw();
+    }
+
+    /// Url of cell at {col}x{row} and columns it spans, from `url` of
+    /// highlights, or found in text if `g:rv_window_detect_links` is set.
+    pub fn link_at(&self, col: usize, row: usize) -> Option<(String, Range<usize>)> {
+        let textbuf = self.textbuf();
+        let hldefs = textbuf.hldefs()?;
+        let hldefs = hldefs.read();
+        let detect = SETTINGS.get::<WindowSettings>().detect_links;
+        textbuf.lines().get(row)?.link_at(col, &hldefs, detect)
+    }
+
+    /// Link at row and columns under pointer, underlined with hand pointer.
+    pub fn set_hover_link(&self, link: Option<(usize, Range<usize>)>) {
+        let link = link.map(|(row, cols)| (row, cols.start, cols.end));
+        if self.imp().set_hover_link(link) == link {
+            return;
+        }
+        self.set_cursor_from_name(link.map(|_| "pointer"));
+        self.queue_draw();
+    }
+
+    /// Show overlay scrollbar, fading out after a while.
+    pub fn flash_scrollbar(&self) {
+        // stays opaque for the first part, then fades out.
+        const HOLD: f64 = 0.6;
+        self.animate("scrollbar", 1500, |view, progress| {
+            let opacity = ((1. - progress) / (1. - HOLD)).min(1.);
+            view.imp().set_scrollbar_opacity(opacity);
+            view.queue_draw();
+        });
+    }
+
+    /// Whether {x}x{y} is on overlay scrollbar.
+    pub fn scrollbar_contains(&self, x: f64, _y: f64) -> bool {
+        let width = self.width() as f64;
+        self.imp().scrollbar_thumb().is_some()
+            && x >= width - imp::SCROLLBAR_WIDTH - 2. * imp::SCROLLBAR_MARGIN
+    }
+
+    /// Top and length of scrollbar thumb in pixels.
+    pub fn scrollbar_thumb(&self) -> Option<(f64, f64)> {
+        self.imp().scrollbar_thumb()
+    }
+
+    /// Top line of window with scrollbar thumb at {y}.
+    pub fn scrollbar_line(&self, y: f64) -> Option<u64> {
+        self.imp().scrollbar_line(y).map(|line| line as u64)
+    }
+
+    pub fn stop_scrolling(&self) {
+        scheduler().cancel(self.job("scroll"));
+        self.imp().clear_scrollback();
+        self.queue_draw();
+    }
+
+    /// Name of scheduler job `kind` of this view.
+    fn job(&self, kind: &str) -> String {
+        // grid ids are reused by messages and default views, the object is not.
+        format!("grid-{}-{:p}", kind, self.as_ptr())
+    }
+
+    /// Call `step` with progress from 0. to 1. in `duration` milliseconds on
+    /// frame clock, restarting the running animation of same `kind`.
+    fn animate<F>(&self, kind: &str, duration: u32, step: F)
+    where
+        F: Fn(&VimGridView, f64) + 'static,
+    {
+        let view = self.downgrade();
+        let duration = duration.max(1) as f64 / 1000.;
+        let mut elapsed = 0.;
+        scheduler().animate(self.job(kind), move |dt| {
+            let view = match view.upgrade() {
+                Some(view) => view,
+                None => return false,
+            };
+            elapsed += dt;
+            let progress = (elapsed / duration).min(1.);
+            step(&view, progress);
+            progress < 1.
+        });
+    }
 }
@@ -14,6 +14,8 @@ mod imp {
     #[derive(Debug)]
     pub struct HighlightDefinitions {
         styles: RefCell<FxHashMap<u64, crate::style::Style>>,
+        // names of highlight groups which made up the style.
+        groups: RefCell<FxHashMap<u64, Vec<String>>>,
         defaults: Cell<Option<Colors>>,
     }
 
@@ -28,6 +30,7 @@ mod imp {
             styles.insert(0, crate::style::Style::new(defaults));
             HighlightDefinitions {
                 styles: RefCell::new(styles),
+                groups: RefCell::new(FxHashMap::default()),
                 defaults: Some(defaults).into(),
             }
         }
@@ -53,6 +56,15 @@ mod imp {
             self.styles.borrow_mut().insert(k, style);
         }
 
+        pub fn groups(&self, k: u64) -> Option<&[String]> {
+            // SAFETY: already locked by user.
+            let groups = unsafe { &*self.groups.as_ptr() };
+            groups.get(&k).map(|groups| groups.as_slice())
+        }
+        pub fn set_groups(&self, k: u64, groups: Vec<String>) {
+            self.groups.borrow_mut().insert(k, groups);
+        }
+
         pub fn defaults(&self) -> Option<&Colors> {
             unsafe { &*self.defaults.as_ptr() }.as_ref()
         }
@@ -88,6 +100,16 @@ impl HighlightDefinitions {
         self.imp().set(k, style);
     }
 
+    /// Highlight groups of style `k`, from `ext_hlstate` info and
+    /// `hl_group_set`.
+    pub fn groups(&self, k: u64) -> Option<&[String]> {
+        self.imp().groups(k)
+    }
+
+    pub fn set_groups(&self, k: u64, groups: Vec<String>) {
+        self.imp().set_groups(k, groups);
+    }
+
     pub fn defaults(&self) -> Option<&Colors> {
         self.imp().defaults()
     }
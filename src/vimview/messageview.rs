@@ -1,4 +1,7 @@
-use std::{cell::Cell, rc::Rc};
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
 
 use glib::subclass::prelude::*;
 use gtk::prelude::*;
@@ -14,12 +17,14 @@ use crate::{
     metrics::Metrics,
 };
 
-use super::HighlightDefinitions;
+use super::{HighlightDefinitions, TextOptions};
 
 mod imp {
-    use std::{cell::Cell, rc::Rc};
+    use std::{
+        cell::{Cell, RefCell},
+        rc::Rc,
+    };
 
-    use glib::{ffi::g_unichar_iswide, translate::from_glib};
     use gtk::{prelude::*, subclass::prelude::*};
     use once_cell::sync::OnceCell;
     use parking_lot::RwLock;
@@ -27,7 +32,7 @@ mod imp {
     use crate::{
         bridge::{GridLineCell, MessageKind, StyledContent},
         metrics::Metrics,
-        vimview::{HighlightDefinitions, VimGridView},
+        vimview::{HighlightDefinitions, TextOptions, VimGridView},
     };
 
     // #[derive(Derivative)]
@@ -156,6 +161,7 @@ mod imp {
 
     impl VimMessageView {
         pub fn set_styled_context(&self, styled_content: StyledContent) {
+            let options = self.view.textbuf().text_options();
             let (mut max_cols, mut cols, mut rows) = (1, 1, 0);
             let mut lines: Vec<Vec<GridLineCell>> = Vec::new();
             lines.push(Vec::new());
@@ -168,7 +174,7 @@ mod imp {
                         cols = 0;
                     }
                     for c in line.chars() {
-                        let double_width: bool = unsafe { from_glib(g_unichar_iswide(c as u32)) };
+                        let double_width = options.is_wide(c);
                         lines[rows].push(GridLineCell {
                             text: String::from(c),
                             hldef: Some(*style),
@@ -210,6 +216,9 @@ mod imp {
         pub fn set_pango_context(&self, pctx: Rc<pango::Context>) {
             self.view.textbuf().set_pango_context(pctx);
         }
+        pub fn set_text_options(&self, options: Rc<RefCell<TextOptions>>) {
+            self.view.textbuf().set_text_options(options);
+        }
         pub fn set_hldefs(&self, hldefs: Rc<RwLock<HighlightDefinitions>>) {
             self.view.set_hldefs(hldefs)
         }
@@ -236,6 +245,7 @@ impl VimMessageView {
         hldefs: Rc<RwLock<HighlightDefinitions>>,
         metrics: Rc<Cell<Metrics>>,
         pctx: Rc<pango::Context>,
+        options: Rc<RefCell<TextOptions>>,
     ) -> VimMessageView {
         let this: VimMessageView =
             glib::Object::new(&[]).expect("Failed to create `VimMessageView`.");
@@ -247,6 +257,7 @@ impl VimMessageView {
         imp.set_hldefs(hldefs);
         imp.set_metrics(metrics);
         imp.set_pango_context(pctx);
+        imp.set_text_options(options);
         imp.set_styled_context(styled_content);
         this.set_halign(gtk::Align::End);
         this.set_valign(gtk::Align::Start);
@@ -264,6 +275,7 @@ pub struct VimMessage {
     hldefs: Rc<RwLock<HighlightDefinitions>>,
     metrics: Rc<Cell<Metrics>>,
     pctx: Rc<pango::Context>,
+    options: Rc<RefCell<TextOptions>>,
 }
 
 impl VimMessage {
@@ -273,6 +285,7 @@ impl VimMessage {
         hldefs: Rc<RwLock<HighlightDefinitions>>,
         metrics: Rc<Cell<Metrics>>,
         pctx: Rc<pango::Context>,
+        options: Rc<RefCell<TextOptions>>,
     ) -> VimMessage {
         VimMessage {
             kind,
@@ -280,6 +293,7 @@ impl VimMessage {
             hldefs,
             metrics,
             pctx,
+            options,
         }
     }
 
@@ -313,6 +327,7 @@ impl FactoryPrototype for VimMessage {
             self.hldefs.clone(),
             self.metrics.clone(),
             self.pctx.clone(),
+            self.options.clone(),
         );
         view.set_margin_top(metrics.height() as _);
         view.set_margin_end(metrics.width() as _);
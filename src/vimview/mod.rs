@@ -1,7 +1,9 @@
 //mod commandview;
+mod boxdraw;
 mod gridview;
 mod highlights;
 mod messageview;
+mod settings;
 mod textbuf;
 mod widgets;
 
@@ -11,11 +13,12 @@ use std::{
     rc::Rc,
 };
 
-pub use gridview::VimGridView;
+pub use gridview::{draw_underline, VimGridView};
 pub use highlights::HighlightDefinitions;
 pub use messageview::{MessageViewWidgets, VimMessage, VimMessageView};
-pub use textbuf::{TextCell, TextLine};
-pub use widgets::{VimGrid, VimGridWidgets};
+pub use settings::{FontSettings, ScrollSettings, WindowSettings};
+pub use textbuf::{TextCell, TextLine, TextOptions};
+pub use widgets::{CursorHost, VimGrid, VimGridWidgets};
 
 #[derive(Clone, Debug)]
 pub struct TextBuf(Rc<RefCell<textbuf::TextBuf>>);
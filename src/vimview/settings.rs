@@ -0,0 +1,356 @@
+use nvim::Value;
+
+use crate::settings::{setting_group, ParseFromValue};
+
+setting_group! {
+    pub struct ScrollSettings("scroll") {
+        // seconds, zero disables smooth scrolling.
+        animation_length: f32 = 0.3,
+    }
+}
+
+setting_group! {
+    pub struct WindowSettings("window") {
+        // seconds, zero disables animated moving and resizing of windows.
+        animation_length: f32 = 0.15,
+        // one of `alt`, `ctrl`, `shift` or `super` held to drag floats, empty
+        // disables dragging.
+        drag_modifier: String = String::from("alt"),
+        // http(s) and file urls in text open by ctrl+click, as cells with
+        // `url` of highlights do.
+        detect_links: bool = true,
+    }
+}
+
+setting_group! {
+    pub struct FontSettings("font") {
+        // pango font descriptions without size, e.g. `Victor Mono Italic`,
+        // empty keeps guifont. Weight and style default to the cell's.
+        bold: String = String::new(),
+        italic: String = String::new(),
+        bold_italic: String = String::new(),
+        // css weight 100 - 1000 of bold cells.
+        bold_weight: u64 = 600,
+        // font family of highlights with `altfont`.
+        altfont: String = String::new(),
+        // fonts of highlight groups, `{group: {family, scale, weight, features}}`.
+        groups: GroupFonts = GroupFonts::default(),
+        // one of `default`, `none`, `gray` or `subpixel`.
+        antialias: String = String::from("default"),
+        // one of `default`, `none`, `slight`, `medium` or `full`.
+        hint_style: String = String::from("default"),
+        // one of `default`, `on` or `off`.
+        hint_metrics: String = String::from("on"),
+        // one of `default`, `rgb`, `bgr`, `vrgb` or `vbgr`.
+        subpixel_order: String = String::from("default"),
+        // OpenType features by font family, `*` for all fonts.
+        features: FontFeatures = FontFeatures::default(),
+        // ligatures under cursor are drawn as separate characters.
+        break_ligatures: bool = false,
+        // box drawing, block, braille and powerline characters are drawn to
+        // fill cells instead of taken from fonts.
+        box_drawing: bool = true,
+        // glyphs wider than their cell, like icons, overflow into following
+        // blank cell of same background instead of overlapping neighbours.
+        icon_overflow: bool = false,
+    }
+}
+
+impl FontSettings {
+    /// Names of settings in `g:rv_font_{name}` changing font options.
+    pub const FONT_OPTIONS: [&'static str; 4] =
+        ["antialias", "hint_style", "hint_metrics", "subpixel_order"];
+
+    /// Cairo font options of antialias, hinting and subpixel order.
+    pub fn font_options(&self) -> Option<cairo::FontOptions> {
+        let mut options = cairo::FontOptions::new().ok()?;
+        options.set_antialias(choose(
+            "antialias",
+            &self.antialias,
+            &[
+                ("default", cairo::Antialias::Default),
+                ("none", cairo::Antialias::None),
+                ("gray", cairo::Antialias::Gray),
+                ("subpixel", cairo::Antialias::Subpixel),
+            ],
+        ));
+        options.set_hint_style(choose(
+            "hint_style",
+            &self.hint_style,
+            &[
+                ("default", cairo::HintStyle::Default),
+                ("none", cairo::HintStyle::None),
+                ("slight", cairo::HintStyle::Slight),
+                ("medium", cairo::HintStyle::Medium),
+                ("full", cairo::HintStyle::Full),
+            ],
+        ));
+        options.set_hint_metrics(choose(
+            "hint_metrics",
+            &self.hint_metrics,
+            &[
+                ("default", cairo::HintMetrics::Default),
+                ("on", cairo::HintMetrics::On),
+                ("off", cairo::HintMetrics::Off),
+            ],
+        ));
+        options.set_subpixel_order(choose(
+            "subpixel_order",
+            &self.subpixel_order,
+            &[
+                ("default", cairo::SubpixelOrder::Default),
+                ("rgb", cairo::SubpixelOrder::Rgb),
+                ("bgr", cairo::SubpixelOrder::Bgr),
+                ("vrgb", cairo::SubpixelOrder::Vrgb),
+                ("vbgr", cairo::SubpixelOrder::Vbgr),
+            ],
+        ));
+        Some(options)
+    }
+}
+
+// value of `choices` named `value`, the first one if none.
+fn choose<T: Copy>(setting: &str, value: &str, choices: &[(&str, T)]) -> T {
+    if let Some((_, choice)) = choices.iter().find(|(name, _)| *name == value) {
+        return *choice;
+    }
+    log::error!(
+        "Invalid font_{} '{}', expected one of {}",
+        setting,
+        value,
+        choices
+            .iter()
+            .map(|(name, _)| *name)
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    choices[0].1
+}
+
+/// Font of cells styled by a highlight group, unset fields keep the cell's.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GroupFont {
+    pub family: Option<String>,
+    // relative to size of guifont.
+    pub scale: Option<f64>,
+    // css weight.
+    pub weight: Option<u64>,
+    // OpenType features, e.g. `ss01, zero`.
+    pub features: Option<String>,
+}
+
+impl GroupFont {
+    fn parse(value: Value) -> Option<GroupFont> {
+        let mut font = GroupFont::default();
+        for (k, v) in value.as_map()? {
+            match k.as_str()? {
+                "family" => font.family = v.as_str().map(String::from),
+                "scale" => font.scale = v.as_f64().or_else(|| v.as_i64().map(|v| v as f64)),
+                "weight" => font.weight = v.as_u64(),
+                "features" => font.features = v.as_str().map(String::from),
+                key => {
+                    log::error!("Unknown key '{}' of group font", key);
+                    return None;
+                }
+            }
+        }
+        Some(font)
+    }
+}
+
+impl From<GroupFont> for Value {
+    fn from(font: GroupFont) -> Self {
+        let mut map = Vec::new();
+        if let Some(family) = font.family {
+            map.push((Value::from("family"), Value::from(family)));
+        }
+        if let Some(scale) = font.scale {
+            map.push((Value::from("scale"), Value::from(scale)));
+        }
+        if let Some(weight) = font.weight {
+            map.push((Value::from("weight"), Value::from(weight)));
+        }
+        if let Some(features) = font.features {
+            map.push((Value::from("features"), Value::from(features)));
+        }
+        Value::Map(map)
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GroupFonts(Vec<(String, GroupFont)>);
+
+impl GroupFonts {
+    /// Font of the first of `groups` having one, `@a.b.c` falls back to
+    /// `@a.b` and `@a` like treesitter captures.
+    pub fn find(&self, groups: &[String]) -> Option<&GroupFont> {
+        groups.iter().find_map(|group| {
+            let mut name = group.as_str();
+            loop {
+                if let Some((_, font)) = self.0.iter().find(|(group, _)| group == name) {
+                    return Some(font);
+                }
+                name = &name[..name.rfind('.')?];
+            }
+        })
+    }
+}
+
+impl ParseFromValue for GroupFonts {
+    fn parse_from_value(&mut self, value: Value) {
+        let map = match value {
+            Value::Map(map) => map,
+            // empty dictionary from vimscript.
+            Value::Array(array) if array.is_empty() => Vec::new(),
+            value => {
+                log::error!("Setting expected a dictionary, but received {:?}", value);
+                return;
+            }
+        };
+        let mut fonts = Vec::with_capacity(map.len());
+        for (group, font) in map {
+            match (group.as_str(), GroupFont::parse(font.clone())) {
+                (Some(group), Some(font)) => fonts.push((group.to_string(), font)),
+                _ => log::error!("Invalid font {:?} of group {:?}", font, group),
+            }
+        }
+        self.0 = fonts;
+    }
+}
+
+impl From<GroupFonts> for Value {
+    fn from(fonts: GroupFonts) -> Self {
+        Value::Map(
+            fonts
+                .0
+                .into_iter()
+                .map(|(group, font)| (Value::from(group), Value::from(font)))
+                .collect(),
+        )
+    }
+}
+
+/// OpenType features like `calt=0, ss01, zero` of font families.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FontFeatures(Vec<(String, String)>);
+
+impl FontFeatures {
+    /// Features of `family` and of all fonts, separated by commas.
+    pub fn get(&self, family: &str) -> Option<String> {
+        let features: Vec<&str> = self
+            .0
+            .iter()
+            .filter(|(name, _)| name == "*" || name.eq_ignore_ascii_case(family))
+            .map(|(_, features)| features.as_str())
+            .filter(|features| !features.trim().is_empty())
+            .collect();
+        Some(features.join(", ")).filter(|features| !features.is_empty())
+    }
+}
+
+impl ParseFromValue for FontFeatures {
+    fn parse_from_value(&mut self, value: Value) {
+        let map = match value {
+            // features of all fonts.
+            Value::String(_) => vec![(Value::from("*"), value)],
+            Value::Map(map) => map,
+            Value::Array(array) if array.is_empty() => Vec::new(),
+            value => {
+                log::error!(
+                    "Setting expected a string or dictionary, but received {:?}",
+                    value
+                );
+                return;
+            }
+        };
+        self.0 = map
+            .into_iter()
+            .filter_map(
+                |(family, features)| match (family.as_str(), features.as_str()) {
+                    (Some(family), Some(features)) => {
+                        Some((family.to_string(), features.to_string()))
+                    }
+                    _ => {
+                        log::error!("Invalid features {:?} of font {:?}", features, family);
+                        None
+                    }
+                },
+            )
+            .collect();
+    }
+}
+
+impl From<FontFeatures> for Value {
+    fn from(features: FontFeatures) -> Self {
+        Value::Map(
+            features
+                .0
+                .into_iter()
+                .map(|(family, features)| (Value::from(family), Value::from(features)))
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_group_fonts() {
+        let mut fonts = GroupFonts::default();
+        fonts.parse_from_value(Value::Map(vec![
+            (
+                Value::from("Comment"),
+                Value::Map(vec![(Value::from("family"), Value::from("Serif"))]),
+            ),
+            (
+                Value::from("@markup.heading"),
+                Value::Map(vec![
+                    (Value::from("scale"), Value::from(1.2)),
+                    (Value::from("weight"), Value::from(700)),
+                ]),
+            ),
+        ]));
+        let groups = |names: &[&str]| {
+            names
+                .iter()
+                .map(|name| name.to_string())
+                .collect::<Vec<_>>()
+        };
+
+        let font = fonts.find(&groups(&["Comment"])).unwrap();
+        assert_eq!(font.family.as_deref(), Some("Serif"));
+        let font = fonts
+            .find(&groups(&["Title", "@markup.heading.1.markdown"]))
+            .unwrap();
+        assert_eq!(font.scale, Some(1.2));
+        assert_eq!(font.weight, Some(700));
+        assert!(fonts.find(&groups(&["@markup", "Normal"])).is_none());
+
+        let value = Value::from(fonts.clone());
+        let mut parsed = GroupFonts::default();
+        parsed.parse_from_value(value);
+        assert_eq!(parsed, fonts);
+    }
+
+    #[test]
+    fn test_font_features() {
+        let mut features = FontFeatures::default();
+        features.parse_from_value(Value::from("zero"));
+        assert_eq!(features.get("Fira Code").as_deref(), Some("zero"));
+
+        features.parse_from_value(Value::Map(vec![
+            (Value::from("*"), Value::from("zero")),
+            (Value::from("fira code"), Value::from("calt=0, ss01")),
+        ]));
+        assert_eq!(
+            features.get("Fira Code").as_deref(),
+            Some("zero, calt=0, ss01")
+        );
+        assert_eq!(features.get("Iosevka").as_deref(), Some("zero"));
+
+        features.parse_from_value(Value::Map(vec![]));
+        assert_eq!(features.get("Iosevka"), None);
+    }
+}
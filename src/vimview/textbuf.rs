@@ -1,14 +1,21 @@
-use std::cell::Cell;
-use std::ops::{Deref, DerefMut};
+use std::borrow::Cow;
+use std::cell::{Cell, RefCell};
+use std::ops::{Deref, DerefMut, Range};
 use std::rc::Rc;
 
 use glib::subclass::prelude::*;
+use once_cell::sync::Lazy;
 use parking_lot::RwLock;
+use regex::Regex;
 
+use super::boxdraw;
 use super::highlights::HighlightDefinitions;
+use super::settings::{FontSettings, GroupFonts};
+use crate::guifont::GuiFont;
+use crate::settings::SETTINGS;
 
 mod imp {
-    use std::cell::Cell;
+    use std::cell::{Cell, RefCell};
     use std::rc::Rc;
 
     use glib::subclass::prelude::*;
@@ -31,6 +38,13 @@ mod imp {
 
         #[derivative(Debug = "ignore")]
         pctx: Option<Rc<pango::Context>>,
+
+        // options of nvim, as `guifontwide` for double width cells.
+        options: Option<Rc<RefCell<super::TextOptions>>>,
+
+        // fonts built from options and metrics, rebuilt when options change
+        // and dropped by `reset_cache` for font settings and metrics.
+        fonts: Option<(super::TextOptions, Rc<super::CellFonts>)>,
     }
 
     impl Default for _TextBuf {
@@ -49,6 +63,8 @@ mod imp {
                 pctx: None,
                 hldefs: None,
                 metrics: None,
+                options: None,
+                fonts: None,
             }
         }
 
@@ -57,12 +73,15 @@ mod imp {
         }
 
         fn reset_cache(&mut self) {
+            self.fonts = None;
+            let fonts = self.fonts();
             let pctx = self.pctx.as_ref().unwrap();
             let hldefs = self.hldefs.as_ref().unwrap().read();
             let metrics = self.metrics.as_ref().unwrap().get();
             self.cells.iter_mut().for_each(|line| {
+                line.clear_cache();
                 line.iter_mut().for_each(|cell| {
-                    cell.reset_attrs(pctx, &hldefs, &metrics);
+                    cell.reset_attrs(pctx, &hldefs, &metrics, &fonts);
                 });
             });
         }
@@ -73,10 +92,37 @@ mod imp {
 
         pub fn set_metrics(&mut self, metrics: Rc<Cell<crate::metrics::Metrics>>) {
             self.metrics.replace(metrics);
+            self.fonts = None;
         }
 
         pub fn set_pango_context(&mut self, pctx: Rc<pango::Context>) {
             self.pctx.replace(pctx);
+            self.fonts = None;
+        }
+
+        pub fn set_text_options(&mut self, options: Rc<RefCell<super::TextOptions>>) {
+            self.options.replace(options);
+            self.fonts = None;
+        }
+
+        pub fn text_options(&self) -> super::TextOptions {
+            self.options
+                .as_ref()
+                .map(|options| options.borrow().clone())
+                .unwrap_or_default()
+        }
+
+        fn fonts(&mut self) -> Rc<super::CellFonts> {
+            let options = self.text_options();
+            match self.fonts.as_ref() {
+                Some((cached, fonts)) if *cached == options => return fonts.clone(),
+                _ => {}
+            }
+            let pctx = self.pctx.as_ref().unwrap();
+            let metrics = self.metrics.as_ref().unwrap().get();
+            let fonts = Rc::new(super::CellFonts::new(&options, pctx, &metrics));
+            self.fonts.replace((options, fonts.clone()));
+            fonts
         }
 
         fn set_cells(&mut self, row: usize, col: usize, cells: &[crate::bridge::GridLineCell]) {
@@ -90,11 +136,13 @@ mod imp {
                 );
                 return;
             }
+            let fonts = self.fonts();
             let line = &self.cells[row];
-            line.cache.set(None);
+            line.clear_cache();
             let pctx = self.pctx.as_ref().unwrap();
             let hldefs = self.hldefs.as_ref().unwrap().read();
             let metrics = self.metrics.as_ref().unwrap().get();
+            let options = self.text_options();
             let mut expands = Vec::with_capacity(line.len());
             let mut start_index = line.get(col).map(|cell| cell.start_index).unwrap_or(0);
             for cell in cells.iter() {
@@ -104,6 +152,7 @@ mod imp {
                     repeat,
                     double_width,
                 } = cell;
+                let text = options.presentation(text);
                 for _ in 0..repeat.unwrap_or(1) {
                     // FIXME: invalid start_index
                     let end_index = start_index + text.len();
@@ -116,7 +165,7 @@ mod imp {
                         start_index,
                         end_index,
                     };
-                    cell.reset_attrs(pctx, &hldefs, &metrics);
+                    cell.reset_attrs(pctx, &hldefs, &metrics, &fonts);
                     log::trace!(
                         "Setting cell {}x{} start_index {} end_index {}",
                         row,
@@ -155,23 +204,96 @@ mod imp {
             line.iter_mut().fold(0, |start_index, cell| {
                 cell.start_index = start_index;
                 cell.end_index = start_index + cell.text.len();
-                cell.reset_attrs(pctx, &hldefs, &metrics);
+                cell.reset_attrs(pctx, &hldefs, &metrics, &fonts);
                 cell.end_index
             });
+            line.update_procedural();
         }
 
-        /// drop head of {} rows. leave tail as empty.
-        fn up(&mut self, rows: usize) {
-            let mut cells = _TextBuf::make(self.rows, self.cols);
-            cells[..(self.rows - rows)].swap_with_slice(&mut self.cells[rows..]);
-            self.cells = cells;
-        }
+        /// scroll region `top..bottom` x `left..right` by `rows` and `cols`.
+        /// positive `rows` moves content up, positive `cols` moves content left.
+        /// cells scrolled into view keep stale content until nvim redraws them.
+        /// returns lines which scrolled out of the region.
+        fn scroll(
+            &mut self,
+            top: usize,
+            bottom: usize,
+            left: usize,
+            right: usize,
+            rows: isize,
+            cols: isize,
+        ) -> Vec<super::TextLine> {
+            let bottom = bottom.min(self.rows);
+            let right = right.min(self.cols);
+            if top >= bottom || left >= right {
+                log::error!(
+                    "invalid scroll region {}..{}x{}..{} of {}x{}",
+                    top,
+                    bottom,
+                    left,
+                    right,
+                    self.rows,
+                    self.cols
+                );
+                return Vec::new();
+            }
+
+            let scrolled = if rows > 0 {
+                let end = (top + rows as usize).min(bottom);
+                self.cells[top..end].to_vec()
+            } else if rows < 0 {
+                let start = bottom.saturating_sub(rows.unsigned_abs()).max(top);
+                self.cells[start..bottom].to_vec()
+            } else {
+                Vec::new()
+            };
+
+            if rows > 0 {
+                let rows = rows as usize;
+                for row in top..bottom.saturating_sub(rows) {
+                    let src = self.cells[row + rows][left..right].to_vec();
+                    self.cells[row][left..right].clone_from_slice(&src);
+                }
+            } else if rows < 0 {
+                let rows = rows.unsigned_abs();
+                for row in ((top + rows)..bottom).rev() {
+                    let src = self.cells[row - rows][left..right].to_vec();
+                    self.cells[row][left..right].clone_from_slice(&src);
+                }
+            }
+
+            if cols > 0 {
+                let cols = cols as usize;
+                for row in top..bottom {
+                    if left + cols < right {
+                        self.cells[row][left..right].rotate_left(cols);
+                    }
+                }
+            } else if cols < 0 {
+                let cols = cols.unsigned_abs();
+                for row in top..bottom {
+                    if left + cols < right {
+                        self.cells[row][left..right].rotate_right(cols);
+                    }
+                }
+            }
+
+            let fonts = self.fonts();
+            let pctx = self.pctx.as_ref().unwrap();
+            let hldefs = self.hldefs.as_ref().unwrap().read();
+            let metrics = self.metrics.as_ref().unwrap().get();
+            self.cells[top..bottom].iter_mut().for_each(|line| {
+                line.clear_cache();
+                line.iter_mut().fold(0, |start_index, cell| {
+                    cell.start_index = start_index;
+                    cell.end_index = start_index + cell.text.len();
+                    cell.reset_attrs(pctx, &hldefs, &metrics, &fonts);
+                    cell.end_index
+                });
+                line.update_procedural();
+            });
 
-        /// drop tail of {} rows. leave head as empty.
-        fn down(&mut self, rows: usize) {
-            let mut cells = _TextBuf::make(self.rows, self.cols);
-            cells[rows..].swap_with_slice(&mut self.cells[..(self.rows - rows)]);
-            self.cells = cells;
+            scrolled
         }
 
         fn pango_context(&self) -> Rc<pango::Context> {
@@ -198,11 +320,18 @@ mod imp {
     }
 
     impl TextBuf {
-        pub(super) fn up(&self, rows: usize) {
-            self.inner.write().up(rows);
-        }
-        pub(super) fn down(&self, rows: usize) {
-            self.inner.write().down(rows);
+        pub(super) fn scroll(
+            &self,
+            top: usize,
+            bottom: usize,
+            left: usize,
+            right: usize,
+            rows: isize,
+            cols: isize,
+        ) -> Vec<super::TextLine> {
+            self.inner
+                .write()
+                .scroll(top, bottom, left, right, rows, cols)
         }
 
         pub(super) fn set_cells(
@@ -226,6 +355,14 @@ mod imp {
             self.inner.write().set_pango_context(pctx);
         }
 
+        pub(super) fn set_text_options(&self, options: Rc<RefCell<super::TextOptions>>) {
+            self.inner.write().set_text_options(options);
+        }
+
+        pub(super) fn text_options(&self) -> super::TextOptions {
+            self.inner.read().text_options()
+        }
+
         pub(super) fn pango_context(&self) -> Rc<pango::Context> {
             self.inner.write().pango_context()
         }
@@ -394,6 +531,16 @@ impl TextBuf {
         self.imp().set_pango_context(pctx);
     }
 
+    /// Options of nvim changing text of cells, shared with the app so
+    /// changes apply after `reset_cache`.
+    pub fn set_text_options(&self, options: Rc<RefCell<TextOptions>>) {
+        self.imp().set_text_options(options);
+    }
+
+    pub fn text_options(&self) -> TextOptions {
+        self.imp().text_options()
+    }
+
     pub fn pango_context(&self) -> Rc<pango::Context> {
         self.imp().pango_context()
     }
@@ -402,12 +549,16 @@ impl TextBuf {
         self.imp().cell(row, col)
     }
 
-    pub fn up(&self, rows: usize) {
-        self.imp().up(rows);
-    }
-
-    pub fn down(&self, rows: usize) {
-        self.imp().down(rows);
+    pub fn scroll(
+        &self,
+        top: usize,
+        bottom: usize,
+        left: usize,
+        right: usize,
+        rows: isize,
+        cols: isize,
+    ) -> Vec<TextLine> {
+        self.imp().scroll(top, bottom, left, right, rows, cols)
     }
 
     pub fn reset_cache(&self) {
@@ -415,6 +566,192 @@ impl TextBuf {
     }
 }
 
+/// Options of nvim changing how text of cells is drawn.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextOptions {
+    /// `guifontwide`, for double width cells.
+    pub font_wide: Option<GuiFont>,
+    /// `ambiwidth` is `double`, ambiguous characters are double width.
+    pub ambiwidth_double: bool,
+    /// `emoji`, emoji are double width in color presentation.
+    pub emoji: bool,
+    /// `arabicshape`, arabic is shaped in contextual forms.
+    pub arabicshape: bool,
+}
+
+impl Default for TextOptions {
+    fn default() -> Self {
+        TextOptions {
+            font_wide: None,
+            ambiwidth_double: false,
+            emoji: true,
+            arabicshape: true,
+        }
+    }
+}
+
+impl TextOptions {
+    /// Whether `c` takes two cells.
+    pub fn is_wide(&self, c: char) -> bool {
+        use glib::ffi::{g_unichar_iswide, g_unichar_iswide_cjk};
+        use glib::translate::from_glib;
+        if !self.emoji && is_emoji(c) {
+            return false;
+        }
+        if self.ambiwidth_double {
+            unsafe { from_glib(g_unichar_iswide_cjk(c as u32)) }
+        } else {
+            unsafe { from_glib(g_unichar_iswide(c as u32)) }
+        }
+    }
+
+    /// Text of cell with variation selector of text presentation for
+    /// emoji, unless `emoji` is set or a selector is given.
+    fn presentation<'t>(&self, text: &'t str) -> Cow<'t, str> {
+        let first = text.chars().next();
+        if self.emoji
+            || !first.map(is_emoji).unwrap_or(false)
+            || text.contains(|c| c == VS15 || c == VS16)
+        {
+            Cow::Borrowed(text)
+        } else {
+            let mut text = text.to_string();
+            text.push(VS15);
+            Cow::Owned(text)
+        }
+    }
+}
+
+/// Fonts of cells other than the default one, from `guifontwide` and
+/// [`FontSettings`], including fonts of highlight groups.
+#[derive(Debug)]
+pub(super) struct CellFonts {
+    // guifontwide.
+    wide: Option<GuiFont>,
+    // scale of guifontwide fitting its glyphs to two cells.
+    wide_scale: f64,
+    // arabic letters may join in contextual forms.
+    arabicshape: bool,
+    bold: Option<pango::FontDescription>,
+    italic: Option<pango::FontDescription>,
+    bold_italic: Option<pango::FontDescription>,
+    bold_weight: pango::Weight,
+    // family of `altfont` highlights.
+    altfont: Option<String>,
+    groups: GroupFonts,
+    // characters of [`boxdraw`] are not drawn from fonts.
+    box_drawing: bool,
+}
+
+impl CellFonts {
+    fn new(
+        options: &TextOptions,
+        pctx: &pango::Context,
+        metrics: &crate::metrics::Metrics,
+    ) -> CellFonts {
+        let settings = SETTINGS.get::<FontSettings>();
+        let parse = |desc: &str| {
+            Some(desc.trim())
+                .filter(|desc| !desc.is_empty())
+                .map(pango::FontDescription::from_string)
+        };
+        CellFonts {
+            wide: options.font_wide.clone(),
+            wide_scale: options
+                .font_wide
+                .as_ref()
+                .map(|font| wide_scale(font, pctx, metrics))
+                .unwrap_or(1.),
+            arabicshape: options.arabicshape,
+            bold: parse(&settings.bold),
+            italic: parse(&settings.italic),
+            bold_italic: parse(&settings.bold_italic),
+            bold_weight: weight(settings.bold_weight),
+            altfont: Some(settings.altfont.trim().to_string()).filter(|family| !family.is_empty()),
+            groups: settings.groups,
+            box_drawing: settings.box_drawing,
+        }
+    }
+
+    fn style(&self, bold: bool, italic: bool) -> Option<&pango::FontDescription> {
+        match (bold, italic) {
+            (true, true) => self.bold_italic.as_ref(),
+            (true, false) => self.bold.as_ref(),
+            (false, true) => self.italic.as_ref(),
+            (false, false) => None,
+        }
+    }
+}
+
+/// Scale of `font` drawing a wide glyph in exactly two cells.
+fn wide_scale(font: &GuiFont, pctx: &pango::Context, metrics: &crate::metrics::Metrics) -> f64 {
+    let base = pctx
+        .font_description()
+        .unwrap_or_else(pango::FontDescription::new);
+    let layout = pango::Layout::new(pctx);
+    layout.set_font_description(Some(&font.description(&base)));
+    // CJK ideograph, the usual glyph of guifontwide.
+    layout.set_text("\u{6c34}");
+    let (_, logical) = layout.extents();
+    let width = logical.width() as f64 / pango::SCALE as f64;
+    if width > 0. {
+        2. * metrics.width() / width
+    } else {
+        1.
+    }
+}
+
+// variation selectors of text and emoji presentation.
+const VS15: char = '\u{fe0e}';
+const VS16: char = '\u{fe0f}';
+// disables joining of arabic letters, leaving isolated forms.
+const ARABIC_ISOLATED: &str = "init=0, medi=0, fina=0";
+
+/// Whether `c` may be drawn as emoji, not exactly `Emoji` of unicode but
+/// blocks of pictographs, symbols and dingbats.
+fn is_emoji(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x2600..=0x27bf | 0x2b00..=0x2bff | 0x1f000..=0x1f02f | 0x1f0a0..=0x1f0ff
+            | 0x1f170..=0x1f1ff | 0x1f300..=0x1f64f | 0x1f680..=0x1f6ff | 0x1f900..=0x1faff
+    )
+}
+
+fn is_arabic(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x0600..=0x06ff | 0x0750..=0x077f | 0x08a0..=0x08ff | 0xfb50..=0xfdff | 0xfe70..=0xfeff
+    )
+}
+
+fn is_procedural(text: &str) -> bool {
+    let mut chars = text.chars();
+    matches!((chars.next(), chars.next()), (Some(c), None) if boxdraw::is_procedural(c))
+}
+
+// nearest pango weight of css weight.
+fn weight(weight: u64) -> pango::Weight {
+    const WEIGHTS: [(u64, pango::Weight); 12] = [
+        (100, pango::Weight::Thin),
+        (200, pango::Weight::Ultralight),
+        (300, pango::Weight::Light),
+        (350, pango::Weight::Semilight),
+        (380, pango::Weight::Book),
+        (400, pango::Weight::Normal),
+        (500, pango::Weight::Medium),
+        (600, pango::Weight::Semibold),
+        (700, pango::Weight::Bold),
+        (800, pango::Weight::Ultrabold),
+        (900, pango::Weight::Heavy),
+        (1000, pango::Weight::Ultraheavy),
+    ];
+    WEIGHTS
+        .iter()
+        .min_by_key(|(w, _)| (*w as i64 - weight as i64).abs())
+        .map(|(_, weight)| *weight)
+        .unwrap()
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct TextCell {
     pub text: String,
@@ -439,11 +776,26 @@ impl Default for TextCell {
 }
 
 impl TextCell {
+    /// Whether the cell is drawn by [`boxdraw`] instead of the font.
+    pub fn is_procedural(&self) -> bool {
+        is_procedural(&self.text)
+    }
+
+    /// Width of the cell in pixels, double width cells span two columns.
+    pub fn width(&self, metrics: &crate::metrics::Metrics) -> f64 {
+        if self.double_width {
+            2. * metrics.width()
+        } else {
+            metrics.width()
+        }
+    }
+
     fn reset_attrs(
         &mut self,
         _pctx: &pango::Context,
         hldefs: &HighlightDefinitions,
         _metrics: &crate::metrics::Metrics,
+        fonts: &CellFonts,
     ) {
         const U16MAX: f32 = u16::MAX as f32;
 
@@ -467,32 +819,116 @@ impl TextCell {
                 hldef = style;
             }
         }
+        let font = fonts.style(hldef.bold, hldef.italic);
+        if let Some(family) = font.and_then(|desc| desc.family()) {
+            let mut attr = pango::AttrString::new_family(&family);
+            attr.set_start_index(start_index);
+            attr.set_end_index(end_index);
+            attrs.insert(attr);
+        }
         if hldef.italic {
-            let mut attr = pango::AttrInt::new_style(pango::Style::Italic);
+            let style = font
+                .map(|desc| desc.style())
+                .filter(|style| *style != pango::Style::Normal)
+                .unwrap_or(pango::Style::Italic);
+            let mut attr = pango::AttrInt::new_style(style);
             attr.set_start_index(start_index);
             attr.set_end_index(end_index);
             attrs.insert(attr);
         }
         if hldef.bold {
-            let mut attr = pango::AttrInt::new_weight(pango::Weight::Semibold);
+            let weight = font
+                .map(|desc| desc.weight())
+                .filter(|weight| *weight != pango::Weight::Normal)
+                .unwrap_or(fonts.bold_weight);
+            let mut attr = pango::AttrInt::new_weight(weight);
             attr.set_start_index(start_index);
             attr.set_end_index(end_index);
             attrs.insert(attr);
         }
-        if hldef.strikethrough {
-            let mut attr = pango::AttrInt::new_strikethrough(true);
+        if let Some(family) = fonts.altfont.as_deref().filter(|_| hldef.altfont) {
+            let mut attr = pango::AttrString::new_family(family);
             attr.set_start_index(start_index);
             attr.set_end_index(end_index);
             attrs.insert(attr);
         }
-        if hldef.underline {
-            let mut attr = pango::AttrInt::new_underline(pango::Underline::Single);
+        if let Some(font) = self
+            .hldef
+            .and_then(|id| hldefs.groups(id))
+            .and_then(|groups| fonts.groups.find(groups))
+        {
+            if let Some(ref family) = font.family {
+                let mut attr = pango::AttrString::new_family(family);
+                attr.set_start_index(start_index);
+                attr.set_end_index(end_index);
+                attrs.insert(attr);
+            }
+            if let Some(scale) = font.scale {
+                let mut attr = pango::AttrFloat::new_scale(scale);
+                attr.set_start_index(start_index);
+                attr.set_end_index(end_index);
+                attrs.insert(attr);
+            }
+            if let Some(w) = font.weight {
+                let mut attr = pango::AttrInt::new_weight(weight(w));
+                attr.set_start_index(start_index);
+                attr.set_end_index(end_index);
+                attrs.insert(attr);
+            }
+            if let Some(ref features) = font.features {
+                let mut attr = pango::AttrFontFeatures::new(features);
+                attr.set_start_index(start_index);
+                attr.set_end_index(end_index);
+                attrs.insert(attr);
+            }
+        }
+        if let Some(font) = fonts.wide.as_ref().filter(|_| self.double_width) {
+            if !font.families.is_empty() {
+                let mut attr = pango::AttrString::new_family(&font.families.join(","));
+                attr.set_start_index(start_index);
+                attr.set_end_index(end_index);
+                attrs.insert(attr);
+            }
+            if let Some(size) = font.size {
+                let mut attr = pango::AttrSize::new((size * pango::SCALE as f64).round() as i32);
+                attr.set_start_index(start_index);
+                attr.set_end_index(end_index);
+                attrs.insert(attr);
+            }
+            if font.bold {
+                let mut attr = pango::AttrInt::new_weight(fonts.bold_weight);
+                attr.set_start_index(start_index);
+                attr.set_end_index(end_index);
+                attrs.insert(attr);
+            }
+            if font.italic {
+                let mut attr = pango::AttrInt::new_style(pango::Style::Italic);
+                attr.set_start_index(start_index);
+                attr.set_end_index(end_index);
+                attrs.insert(attr);
+            }
+            if (fonts.wide_scale - 1.).abs() > 0.01 {
+                let mut attr = pango::AttrFloat::new_scale(fonts.wide_scale);
+                attr.set_start_index(start_index);
+                attr.set_end_index(end_index);
+                attrs.insert(attr);
+            }
+        }
+        if !fonts.arabicshape && self.text.chars().next().map(is_arabic).unwrap_or(false) {
+            let mut attr = pango::AttrFontFeatures::new(ARABIC_ISOLATED);
+            attr.set_start_index(start_index);
+            attr.set_end_index(end_index);
+            attrs.insert(attr);
+        }
+        if fonts.box_drawing && is_procedural(&self.text) {
+            // drawn by view, pango draws its background only.
+            let mut attr = pango::AttrInt::new_foreground_alpha(1);
             attr.set_start_index(start_index);
             attr.set_end_index(end_index);
             attrs.insert(attr);
         }
-        if hldef.undercurl {
-            let mut attr = pango::AttrInt::new_underline(pango::Underline::Error);
+        if hldef.strikethrough {
+            let mut attr = pango::AttrInt::new_strikethrough(true);
             attr.set_start_index(start_index);
             attr.set_end_index(end_index);
             attrs.insert(attr);
@@ -527,16 +963,6 @@ impl TextCell {
             attr.set_end_index(end_index);
             attrs.insert(attr);
         }
-        if let Some(special) = hldef.colors.special.or(default_colors.special) {
-            let mut attr = pango::AttrColor::new_underline_color(
-                (special.red() * U16MAX).round() as u16,
-                (special.green() * U16MAX).round() as u16,
-                (special.blue() * U16MAX).round() as u16,
-            );
-            attr.set_start_index(start_index);
-            attr.set_end_index(end_index);
-            attrs.insert(attr);
-        }
 
         self.attrs = attrs.attributes();
     }
@@ -546,6 +972,10 @@ impl TextCell {
 pub struct TextLine {
     boxed: Box<[TextCell]>,
     cache: Cell<Option<(pango::Layout, pango::LayoutLine)>>,
+    // any cell is drawn by [`boxdraw`].
+    procedural: bool,
+    // urls found in text and columns they span, cleared with `cache`.
+    links: RefCell<Option<Vec<(String, Range<usize>)>>>,
 }
 
 impl Clone for TextLine {
@@ -553,6 +983,8 @@ impl Clone for TextLine {
         TextLine {
             boxed: self.boxed.clone(),
             cache: Cell::new(unsafe { &*self.cache.as_ptr() }.clone()),
+            procedural: self.procedural,
+            links: self.links.clone(),
         }
     }
 }
@@ -568,9 +1000,20 @@ impl TextLine {
         Self {
             boxed: line.into_boxed_slice(),
             cache: Cell::new(None),
+            procedural: false,
+            links: RefCell::new(None),
         }
     }
 
+    /// Whether any cell of the line is drawn by [`boxdraw`].
+    pub fn has_procedural(&self) -> bool {
+        self.procedural
+    }
+
+    fn update_procedural(&mut self) {
+        self.procedural = self.boxed.iter().any(TextCell::is_procedural);
+    }
+
     pub fn cache(&self) -> Option<(pango::Layout, pango::LayoutLine)> {
         unsafe { &*self.cache.as_ptr() }.clone()
     }
@@ -578,6 +1021,70 @@ impl TextLine {
     pub fn set_cache(&self, layout: pango::Layout, line: pango::LayoutLine) {
         self.cache.set((layout, line).into());
     }
+
+    pub fn clear_cache(&self) {
+        self.cache.set(None);
+        self.links.replace(None);
+    }
+
+    /// Url of cell at `col` and columns it spans, from `url` of highlights,
+    /// or found in text if `detect`.
+    pub fn link_at(
+        &self,
+        col: usize,
+        hldefs: &HighlightDefinitions,
+        detect: bool,
+    ) -> Option<(String, Range<usize>)> {
+        let url = |cell: &TextCell| {
+            cell.hldef
+                .and_then(|id| hldefs.get(id))
+                .and_then(|style| style.url.clone())
+        };
+        if let Some(link) = self.get(col).and_then(url) {
+            let same = |c: &usize| url(&self[*c]).as_ref() == Some(&link);
+            let start = (0..col).rev().take_while(same).last().unwrap_or(col);
+            let end = (col..self.len()).take_while(same).last().unwrap_or(col) + 1;
+            return Some((link, start..end));
+        }
+        if !detect || col >= self.len() {
+            return None;
+        }
+        self.links
+            .borrow_mut()
+            .get_or_insert_with(|| self.find_links())
+            .iter()
+            .find(|(_, cols)| cols.contains(&col))
+            .cloned()
+    }
+
+    // urls in text of line and columns they span.
+    fn find_links(&self) -> Vec<(String, Range<usize>)> {
+        // text of line, and column of each byte.
+        let mut text = String::new();
+        let mut cols = Vec::new();
+        for (c, cell) in self.iter().enumerate() {
+            text.push_str(&cell.text);
+            cols.resize(text.len(), c);
+        }
+        find_urls(&text)
+            .map(|found| {
+                // continuation of double width cell belongs to the cell before it.
+                let mut end = cols[found.end - 1] + 1;
+                while self.get(end).filter(|cell| cell.text.is_empty()).is_some() {
+                    end += 1;
+                }
+                (text[found.clone()].to_string(), cols[found.start]..end)
+            })
+            .collect()
+    }
+}
+
+/// Byte ranges of http(s) or file urls in `text`.
+fn find_urls(text: &str) -> impl Iterator<Item = Range<usize>> + '_ {
+    // trailing punctuation belongs to the sentence.
+    static URL: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r#"(?:https?|file)://[^\s<>"'`]*[^\s<>"'`.,;:!?)\]}]"#).unwrap());
+    URL.find_iter(text).map(|m| m.range())
 }
 
 impl Deref for TextLine {
@@ -608,10 +1115,12 @@ impl AsMut<[TextCell]> for TextLine {
 
 impl From<Box<[TextCell]>> for TextLine {
     fn from(boxed: Box<[TextCell]>) -> Self {
-        TextLine {
+        let mut line = TextLine {
             boxed,
             ..Default::default()
-        }
+        };
+        line.update_procedural();
+        line
     }
 }
 
@@ -626,3 +1135,59 @@ impl TextLine {
         self.boxed
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::ops::Range;
+
+    use super::{find_urls, TextCell, TextLine};
+
+    // byte range of url over byte at `index`.
+    fn find_url(text: &str, index: usize) -> Option<Range<usize>> {
+        find_urls(text).find(|range| range.contains(&index))
+    }
+
+    #[test]
+    fn test_find_url() {
+        let text = "see https://github.com/neovim/neovim/pull/1 (or file:///tmp/a.txt).";
+        assert_eq!(find_url(text, 0), None);
+        assert_eq!(find_url(text, 4), Some(4..43));
+        assert_eq!(find_url(text, 42), Some(4..43));
+        assert_eq!(find_url(text, 43), None);
+        assert_eq!(find_url(text, 50), Some(48..65));
+        assert_eq!(find_url("ftp://host", 2), None);
+        assert_eq!(find_url("http://", 2), None);
+    }
+
+    #[test]
+    fn test_find_links() {
+        let cells: Vec<_> = [
+            "见", "", " ", "h", "t", "t", "p", ":", "/", "/", "水", "", ".",
+        ]
+        .iter()
+        .map(|text| TextCell {
+            text: text.to_string(),
+            double_width: *text == "见" || *text == "水",
+            ..TextCell::default()
+        })
+        .collect();
+        let line = TextLine::from(cells.into_boxed_slice());
+        // continuation of the last double width cell is part of the url.
+        assert_eq!(line.find_links(), vec![("http://水".to_string(), 3..12)]);
+    }
+
+    #[test]
+    fn test_cell_width() {
+        let mut metrics = crate::metrics::Metrics::new();
+        metrics.set_width(8.);
+        let mut cell = TextCell {
+            text: "─".to_string(),
+            ..TextCell::default()
+        };
+        assert!(cell.is_procedural());
+        assert_eq!(cell.width(&metrics), 8.);
+        // ambiwidth=double
+        cell.double_width = true;
+        assert_eq!(cell.width(&metrics), 16.);
+    }
+}
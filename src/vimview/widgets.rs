@@ -2,31 +2,58 @@ use std::cell::{Cell, RefCell};
 use std::rc::Rc;
 use std::sync::atomic;
 
+use gtk::gdk;
 use gtk::prelude::*;
 use parking_lot::RwLock;
 use relm4::factory::positions::FixedPosition;
 use relm4::*;
 
 use crate::app::{self, Dragging};
-use crate::bridge::{MouseAction, MouseButton, SerialCommand, UiCommand};
+use crate::bridge::{
+    MouseAction, MouseButton, ParallelCommand, SerialCommand, TxWrapper, UiCommand,
+};
 use crate::event_aggregator::EVENT_AGGREGATOR;
 use crate::grapheme::{Coord, Pos, Rectangle};
+use crate::keys::ToInput;
+use crate::scheduler::scheduler;
+use crate::settings::SETTINGS;
 
 use super::gridview::VimGridView;
-use super::TextBuf;
+use super::settings::{ScrollSettings, WindowSettings};
+use super::{TextBuf, TextLine, TextOptions};
 
 type HighlightDefinitions = Rc<RwLock<crate::vimview::HighlightDefinitions>>;
 
+// scrolled but not animated by view yet.
+struct Scrolled {
+    top: usize,
+    bottom: usize,
+    // zero for scrolling which could not be animated.
+    rows: isize,
+    lines: Vec<TextLine>,
+}
+
 pub struct VimGrid {
     win: u64,
     grid: u64,
     pos: Pos,
     coord: Coord,
     move_to: Cell<Option<FixedPosition>>,
+    // animate the pending move and resize.
+    transit: Cell<bool>,
+    // has been placed by set_coord once.
+    positioned: bool,
+    // window allows animated moves, `w:rv_window_animation`.
+    animatable: bool,
+    scrolls: Cell<Vec<Scrolled>>,
     width: usize,
     height: usize,
     is_float: bool,
     focusable: bool,
+    // stacking of floats, zero for normal windows.
+    zindex: u64,
+    // raise float above others of same zindex.
+    order: u64,
     metrics: Rc<Cell<crate::metrics::Metrics>>,
     font_description: Rc<RefCell<pango::FontDescription>>,
     dragging: Rc<Cell<Option<Dragging>>>,
@@ -34,6 +61,15 @@ pub struct VimGrid {
     textbuf: TextBuf,
 
     visible: bool,
+    // nvim window showing on the grid.
+    window: Option<nvim::Window<TxWrapper>>,
+    // shown in its own toplevel, `ext_multigrid` external window.
+    external: bool,
+    // (top line, bottom line, line count) of window, from win_viewport.
+    viewport: Option<(f64, f64, f64)>,
+    // (row, column) of cursor if on this grid.
+    cursor: Option<(usize, usize)>,
+    cursor_host: Option<CursorHost>,
     // animation: Option<adw::TimedAnimation>,
 }
 
@@ -60,13 +96,24 @@ impl VimGrid {
             width: rect.width as _,
             height: rect.height as _,
             move_to: None.into(),
+            transit: false.into(),
+            positioned: false,
+            animatable: true,
+            scrolls: Vec::new().into(),
             dragging,
             is_float: false,
             focusable: true,
+            zindex: 0,
+            order: 0,
             metrics,
             textbuf,
             visible: true,
             font_description,
+            window: None,
+            external: false,
+            viewport: None,
+            cursor: None,
+            cursor_host: None,
             // animation: None,
         }
     }
@@ -103,36 +150,44 @@ impl VimGrid {
         self.textbuf().borrow().reset_cache();
     }
 
-    // content go up, view go down, eat head of rows.
-    pub fn up(
+    // positive rows: content go up, view go down, eat head of rows.
+    // positive cols: content go left, view go right, eat head of cols.
+    pub fn scroll(
         &mut self,
-        // top: usize,
-        // bottom: usize,
-        // left: usize,
-        // right: usize,
-        rows: usize,
-        // cols: usize,
+        top: usize,
+        bottom: usize,
+        left: usize,
+        right: usize,
+        rows: isize,
+        cols: isize,
     ) {
-        log::debug!("scroll-region {} rows moved up.", rows);
         log::debug!(
-            "Origin Region {:?} {}x{}",
-            self.coord,
-            self.width,
-            self.height
+            "scroll-region {}..{}x{}..{} moved {} rows {} cols.",
+            top,
+            bottom,
+            left,
+            right,
+            rows,
+            cols
         );
-        self.textbuf().borrow_mut().up(rows);
-    }
-
-    // content go down, view go up, eat tail of rows.
-    pub fn down(&mut self, rows: usize) {
-        log::debug!("scroll-region {} rows moved down.", rows);
         log::debug!(
             "Origin Region {:?} {}x{}",
             self.coord,
             self.width,
             self.height
         );
-        self.textbuf().borrow_mut().down(rows);
+        let lines = self
+            .textbuf()
+            .borrow_mut()
+            .scroll(top, bottom, left, right, rows, cols);
+        // only whole lines scrolled vertically are animated.
+        let animatable = cols == 0 && left == 0 && right >= self.width;
+        self.scrolls.get_mut().push(Scrolled {
+            top,
+            bottom,
+            rows: if animatable { rows } else { 0 },
+            lines,
+        });
     }
 
     pub fn resize(&mut self, width: usize, height: usize) {
@@ -148,6 +203,14 @@ impl VimGrid {
         self.pos = pos;
         self.coord = Coord { col, row };
         self.move_to.replace(move_to.into());
+        // first placement and showing up from hidden jump directly.
+        self.transit
+            .set(self.positioned && self.visible && self.animatable);
+        self.positioned = true;
+    }
+
+    pub fn set_animatable(&mut self, animatable: bool) {
+        self.animatable = animatable;
     }
 
     pub fn set_is_float(&mut self, is_float: bool) {
@@ -158,42 +221,251 @@ impl VimGrid {
         self.focusable = focusable;
     }
 
+    pub fn set_zindex(&mut self, zindex: u64, order: u64) {
+        self.zindex = zindex;
+        self.order = order;
+    }
+
+    pub fn zindex(&self) -> u64 {
+        self.zindex
+    }
+
+    pub fn window(&self) -> Option<&nvim::Window<TxWrapper>> {
+        self.window.as_ref()
+    }
+
+    pub fn set_window(&mut self, window: nvim::Window<TxWrapper>) {
+        self.window.replace(window);
+    }
+
+    pub fn is_external(&self) -> bool {
+        self.external
+    }
+
+    pub fn set_external(&mut self, external: bool) {
+        self.external = external;
+    }
+
+    pub fn is_float(&self) -> bool {
+        self.is_float
+    }
+
+    pub fn viewport(&self) -> Option<(f64, f64, f64)> {
+        self.viewport
+    }
+
+    pub fn set_viewport(&mut self, top_line: f64, bottom_line: f64, line_count: f64) {
+        self.viewport.replace((top_line, bottom_line, line_count));
+    }
+
+    pub fn set_cursor(&mut self, cursor: Option<(usize, usize)>) {
+        self.cursor = cursor;
+    }
+
     pub fn set_pango_context(&self, pctx: Rc<pango::Context>) {
         self.textbuf().borrow().set_pango_context(pctx);
     }
+
+    pub fn set_text_options(&self, options: Rc<RefCell<TextOptions>>) {
+        self.textbuf().borrow().set_text_options(options);
+    }
+
+    pub fn set_cursor_host(&mut self, host: CursorHost) {
+        self.cursor_host.replace(host);
+    }
 }
 
+/// Cursor widget, moved into the toplevel of external window having the
+/// cursor and back to where it was.
+#[derive(Clone, Debug)]
+pub struct CursorHost {
+    widget: gtk::Widget,
+    // parent and next sibling the cursor was moved out of.
+    home: Rc<RefCell<Option<(gtk::Widget, Option<gtk::Widget>)>>>,
+}
+
+impl CursorHost {
+    pub fn new(widget: &impl IsA<gtk::Widget>) -> Self {
+        CursorHost {
+            widget: widget.clone().upcast(),
+            home: Rc::new(RefCell::new(None)),
+        }
+    }
+
+    fn attach(&self, overlay: &gtk::Overlay) {
+        if self.widget.parent().as_ref() == Some(overlay.upcast_ref()) {
+            return;
+        }
+        if self.home.borrow().is_none() {
+            if let Some(parent) = self.widget.parent() {
+                self.home
+                    .replace(Some((parent, self.widget.next_sibling())));
+            }
+        }
+        self.widget.unparent();
+        overlay.add_overlay(&self.widget);
+    }
+
+    fn detach(&self, overlay: &gtk::Overlay) {
+        if self.widget.parent().as_ref() != Some(overlay.upcast_ref()) {
+            return;
+        }
+        self.widget.unparent();
+        if let Some((parent, sibling)) = self.home.take() {
+            self.widget.insert_before(&parent, sibling.as_ref());
+        }
+    }
+}
+
+// toplevel of external window, sharing textbuf with the hidden grid view.
 #[derive(Debug)]
-pub struct VimGridWidgets {
+struct ExternalWindow {
+    window: gtk::Window,
+    // holds the cursor over view while the grid has it.
+    overlay: gtk::Overlay,
     view: VimGridView,
+    cursor_host: Option<CursorHost>,
 }
 
-impl factory::FactoryPrototype for VimGrid {
-    type Factory = crate::factory::FactoryMap<Self>;
-    type Widgets = VimGridWidgets;
-    type Root = VimGridView;
-    type View = gtk::Fixed;
-    type Msg = app::AppMessage;
+impl ExternalWindow {
+    fn destroy(self) {
+        if let Some(ref host) = self.cursor_host {
+            host.detach(&self.overlay);
+        }
+        self.window.destroy();
+    }
+}
 
-    fn init_view(&self, grid: &u64, sender: Sender<app::AppMessage>) -> VimGridWidgets {
-        let grid = *grid;
-        view! {
-            view = VimGridView::new(grid, self.width as _, self.height as _) {
-                set_widget_name: &format!("vim-grid-{}-{}", self.win, grid),
-                set_textbuf: self.textbuf.clone(),
+#[derive(Derivative)]
+#[derivative(Debug)]
+pub struct VimGridWidgets {
+    view: VimGridView,
+    external: RefCell<Option<ExternalWindow>>,
+    #[derivative(Debug = "ignore")]
+    sender: Sender<app::AppMessage>,
+}
 
-                set_visible: self.visible,
-                set_can_focus: true,
-                set_focusable: true,
-                set_focus_on_click: true,
+impl Drop for VimGridWidgets {
+    fn drop(&mut self) {
+        if let Some(external) = self.external.take() {
+            external.destroy();
+        }
+    }
+}
 
-                set_overflow: gtk::Overflow::Hidden,
+// modifier held to drag floats, `g:rv_window_drag_modifier`.
+fn drag_modifier() -> Option<gdk::ModifierType> {
+    let settings = SETTINGS.get::<WindowSettings>();
+    match settings.drag_modifier.to_lowercase().as_str() {
+        "alt" | "meta" => Some(gdk::ModifierType::ALT_MASK),
+        "ctrl" | "control" => Some(gdk::ModifierType::CONTROL_MASK),
+        "shift" => Some(gdk::ModifierType::SHIFT_MASK),
+        "super" => Some(gdk::ModifierType::SUPER_MASK),
+        "" => None,
+        modifier => {
+            log::warn!("unknown drag modifier '{}'", modifier);
+            None
+        }
+    }
+}
 
-                set_font_description: &self.font_description.borrow(),
+fn grabs_float(view: &VimGridView, state: gdk::ModifierType) -> bool {
+    view.is_float()
+        && view.is_focusable()
+        && drag_modifier()
+            .map(|modifier| state.contains(modifier))
+            .unwrap_or(false)
+}
 
-                set_css_classes: &["vim-view-grid", &format!("vim-view-grid-{}", self.grid)],
+impl VimGrid {
+    fn connect_pointer(&self, view: &VimGridView, grid: u64, sender: &Sender<app::AppMessage>) {
+        // float grabbed by drag modifier, pointer not sent to nvim.
+        let moving = Rc::new(Cell::new(false));
+        let drag_listener = gtk::GestureDrag::builder()
+            .button(1)
+            .propagation_phase(gtk::PropagationPhase::Capture)
+            .name("float-drag-listener")
+            .build();
+        drag_listener.connect_drag_begin(
+            glib::clone!(@weak view, @strong moving => move |c, _, _| {
+                if grabs_float(&view, c.current_event_state()) {
+                    log::debug!("grid {} float grabbed", grid);
+                    moving.set(true);
+                    view.stop_transition();
+                    c.set_state(gtk::EventSequenceState::Claimed);
+                } else {
+                    c.set_state(gtk::EventSequenceState::Denied);
+                }
+            }),
+        );
+        drag_listener.connect_drag_update(
+            glib::clone!(@weak view, @strong moving => move |_, x, y| {
+                // offset is in coordinates of the moving view, so it stays under pointer.
+                if let Some((left, top)) = view.position().filter(|_| moving.get()) {
+                    view.transition(left + x, top + y, 0);
+                }
+            }),
+        );
+        drag_listener.connect_drag_end(
+            glib::clone!(@weak view, @strong moving, @strong sender, @weak self.metrics as metrics => move |_, _, _| {
+                if !moving.replace(false) {
+                    return;
+                }
+                if let Some((x, y)) = view.position() {
+                    let metrics = metrics.get();
+                    let col = (x / metrics.width()).round().max(0.) as u64;
+                    let row = (y / metrics.height()).round().max(0.) as u64;
+                    log::debug!("grid {} float dropped at {}x{}", grid, col, row);
+                    sender.send(app::AppMessage::MoveWindow { grid, col, row }).unwrap();
+                }
+            }),
+        );
+        view.add_controller(&drag_listener);
+
+        // thumb top minus pointer, and line last scrolled to.
+        let scrollbar_grab: Rc<Cell<Option<(f64, Option<u64>)>>> = Rc::new(Cell::new(None));
+        let scroll_to = glib::clone!(@strong sender, @strong scrollbar_grab => move |view: &VimGridView, y: f64| {
+            if let Some((offset, last)) = scrollbar_grab.get() {
+                let line = view.scrollbar_line(y + offset).filter(|line| Some(*line) != last);
+                if let Some(top_line) = line {
+                    scrollbar_grab.set(Some((offset, line)));
+                    sender.send(app::AppMessage::ScrollWindow { grid, top_line }).unwrap();
+                }
+                view.flash_scrollbar();
             }
-        }
+        });
+        let scrollbar_listener = gtk::GestureDrag::builder()
+            .button(1)
+            .propagation_phase(gtk::PropagationPhase::Capture)
+            .name("scrollbar-listener")
+            .build();
+        scrollbar_listener.connect_drag_begin(
+            glib::clone!(@weak view, @strong scrollbar_grab, @strong scroll_to => move |c, x, y| {
+                let thumb = view.scrollbar_thumb().filter(|_| view.scrollbar_contains(x, y));
+                if let Some((top, length)) = thumb {
+                    // pressed outside of thumb centers it on pointer.
+                    let offset = if top <= y && y < top + length { top - y } else { -length / 2. };
+                    scrollbar_grab.set(Some((offset, None)));
+                    c.set_state(gtk::EventSequenceState::Claimed);
+                    scroll_to(&view, y);
+                } else {
+                    c.set_state(gtk::EventSequenceState::Denied);
+                }
+            }),
+        );
+        scrollbar_listener.connect_drag_update(
+            glib::clone!(@weak view, @strong scroll_to => move |c, _, y| {
+                if let Some((_, start_y)) = c.start_point() {
+                    scroll_to(&view, start_y + y);
+                }
+            }),
+        );
+        scrollbar_listener.connect_drag_end(
+            glib::clone!(@strong scrollbar_grab => move |_, _, _| {
+                scrollbar_grab.set(None);
+            }),
+        );
+        view.add_controller(&scrollbar_listener);
 
         let click_listener = gtk::GestureClick::builder()
             .button(0)
@@ -203,8 +475,11 @@ impl factory::FactoryPrototype for VimGrid {
             .name("click-listener")
             .build();
         click_listener.connect_pressed(
-            glib::clone!(@strong sender, @weak self.dragging as dragging, @weak self.metrics as metrics => move |c, n_press, x, y| {
+            glib::clone!(@strong sender, @strong moving, @strong scrollbar_grab, @weak self.dragging as dragging, @weak self.metrics as metrics => move |c, n_press, x, y| {
                 sender.send(app::AppMessage::ShowPointer).unwrap();
+                if moving.get() || scrollbar_grab.get().is_some() {
+                    return;
+                }
                 let metrics = metrics.get();
                 let width = metrics.width();
                 let height = metrics.height();
@@ -233,7 +508,7 @@ impl factory::FactoryPrototype for VimGrid {
             }),
         );
         click_listener.connect_released(
-            glib::clone!(@strong sender, @weak self.dragging as dragging, @weak self.metrics as metrics => move |c, n_press, x, y| {
+            glib::clone!(@strong sender, @strong moving, @weak self.dragging as dragging, @weak self.metrics as metrics => move |c, n_press, x, y| {
                 sender.send(app::AppMessage::ShowPointer).unwrap();
                 let metrics = metrics.get();
                 let width = metrics.width();
@@ -243,6 +518,10 @@ impl factory::FactoryPrototype for VimGrid {
                 log::trace!("grid {} mouse released {} times at {}x{} -> {}x{}", grid, n_press, x, y, cols, rows);
                 let modifier = c.current_event_state().to_string();
                 dragging.set(None);
+                // press went to moving the float instead.
+                if moving.get() {
+                    return;
+                }
                 let btn = match c.current_button() {
                     1 => MouseButton::Left,
                     2 => MouseButton::Middle,
@@ -263,14 +542,52 @@ impl factory::FactoryPrototype for VimGrid {
         );
         view.add_controller(&click_listener);
 
+        // ctrl+click opens link, instead of clicking in nvim.
+        let link_listener = gtk::GestureClick::builder()
+            .button(1)
+            .propagation_phase(gtk::PropagationPhase::Capture)
+            .name("link-listener")
+            .build();
+        link_listener.connect_pressed(
+            glib::clone!(@weak view, @weak self.metrics as metrics => move |c, _, x, y| {
+                let link = Some(c.current_event_state())
+                    .filter(|state| state.contains(gdk::ModifierType::CONTROL_MASK))
+                    .and_then(|_| {
+                        let metrics = metrics.get();
+                        let col = (x / metrics.width()).floor() as usize;
+                        let row = (y / metrics.height()).floor() as usize;
+                        view.link_at(col, row)
+                    });
+                if let Some((url, _)) = link {
+                    log::info!("grid {} opening {}", grid, url);
+                    c.set_state(gtk::EventSequenceState::Claimed);
+                    let window = view.root().and_then(|root| root.downcast::<gtk::Window>().ok());
+                    gtk::show_uri(window.as_ref(), &url, gdk::CURRENT_TIME);
+                } else {
+                    c.set_state(gtk::EventSequenceState::Denied);
+                }
+            }),
+        );
+        view.add_controller(&link_listener);
+
         let motion_listener = gtk::EventControllerMotion::new();
         let grid_id = grid;
         motion_listener.connect_enter(move |_, _, _| {
             app::GridActived.store(grid_id, atomic::Ordering::Relaxed);
         });
-        motion_listener.connect_motion(glib::clone!(@strong sender, @weak self.dragging as dragging, @weak self.metrics as metrics => move |c, x, y| {
+        motion_listener.connect_motion(glib::clone!(@strong sender, @weak view, @weak self.dragging as dragging, @weak self.metrics as metrics => move |c, x, y| {
             sender.send(app::AppMessage::ShowPointer).unwrap();
+            if view.scrollbar_contains(x, y) {
+                view.flash_scrollbar();
+            }
             log::trace!("cursor motion {} {}", x, y);
+            if dragging.get().is_none() {
+                let metrics = metrics.get();
+                let col = (x / metrics.width()).floor() as usize;
+                let row = (y / metrics.height()).floor() as usize;
+                let link = view.link_at(col, row).map(|(_, cols)| (row, cols));
+                view.set_hover_link(link);
+            }
             if let Some(Dragging { btn, pos }) = dragging.get() {
                 let metrics = metrics.get();
                 let width = metrics.width();
@@ -295,9 +612,135 @@ impl factory::FactoryPrototype for VimGrid {
             // if motion show one second.
 
         }));
+        motion_listener.connect_leave(glib::clone!(@weak view => move |_| {
+            view.set_hover_link(None);
+        }));
         view.add_controller(&motion_listener);
+    }
+
+    fn external_window(&self, grid: u64, widgets: &VimGridWidgets) -> ExternalWindow {
+        let sender = &widgets.sender;
+        let view = VimGridView::new(grid, self.width as _, self.height as _);
+        view.set_widget_name(&format!("vim-grid-{}-{}-external", self.win, grid));
+        view.set_textbuf(self.textbuf.clone());
+        view.set_focusable(true);
+        view.set_font_description(&self.font_description.borrow());
+        view.set_css_classes(&["vim-view-grid", &format!("vim-view-grid-{}", self.grid)]);
+        self.connect_pointer(&view, grid, sender);
+
+        let scrolled = gtk::ScrolledWindow::builder()
+            .hscrollbar_policy(gtk::PolicyType::External)
+            .vscrollbar_policy(gtk::PolicyType::External)
+            .child(&view)
+            .build();
+        let overlay = gtk::Overlay::builder().child(&scrolled).build();
+        let metrics = self.metrics.get();
+        let window = gtk::Window::builder()
+            .title(&format!("grid {}", grid))
+            .default_width((self.width as f64 * metrics.width()).ceil() as i32)
+            .default_height((self.height as f64 * metrics.height()).ceil() as i32)
+            .child(&overlay)
+            .build();
+        if let Some(application) = widgets
+            .view
+            .root()
+            .and_then(|root| root.downcast::<gtk::Window>().ok())
+            .and_then(|root| root.application())
+        {
+            window.set_application(Some(&application));
+        }
 
-        VimGridWidgets { view }
+        let key_controller = gtk::EventControllerKey::builder()
+            .name("vimview-external-key-controller")
+            .build();
+        key_controller.connect_key_pressed(glib::clone!(@strong sender => move |_, keyval, _keycode, modifier| {
+            if let Some(keypress) = (keyval, modifier).to_input() {
+                log::debug!("keypress {} of external grid {} sent to neovim.", keypress, grid);
+                sender.send(UiCommand::Serial(SerialCommand::Keyboard(keypress)).into()).unwrap();
+                gtk::Inhibit(true)
+            } else {
+                gtk::Inhibit(false)
+            }
+        }));
+        window.add_controller(&key_controller);
+
+        window.connect_is_active_notify(glib::clone!(@strong sender => move |window| {
+            if window.is_active() {
+                sender.send(app::AppMessage::FocusWindow(grid)).unwrap();
+                // main window lost focus to this one.
+                sender.send(UiCommand::Parallel(ParallelCommand::FocusGained).into()).unwrap();
+            }
+        }));
+        scheduler().watch(&window);
+        window.connect_close_request(glib::clone!(@strong sender => move |_| {
+            sender.send(app::AppMessage::CloseWindow(grid)).unwrap();
+            gtk::Inhibit(true)
+        }));
+        let resized = glib::clone!(@strong sender, @weak self.metrics as metrics, @weak view => move |window: &gtk::Window| {
+            let metrics = metrics.get();
+            let (width, height) = window.default_size();
+            let cols = (width as f64 / metrics.width()).floor() as u64;
+            let rows = (height as f64 / metrics.height()).floor() as u64;
+            if cols != view.property::<u64>("width") || rows != view.property::<u64>("height") {
+                log::debug!("external grid {} resizing to {}x{}", grid, cols, rows);
+                sender
+                    .send(
+                        UiCommand::Parallel(ParallelCommand::ResizeGrid {
+                            grid,
+                            width: cols,
+                            height: rows,
+                        })
+                        .into(),
+                    )
+                    .unwrap();
+            }
+        });
+        window.connect_default_width_notify(resized.clone());
+        window.connect_default_height_notify(resized);
+
+        ExternalWindow {
+            window,
+            overlay,
+            view,
+            cursor_host: self.cursor_host.clone(),
+        }
+    }
+}
+
+impl factory::FactoryPrototype for VimGrid {
+    type Factory = crate::factory::FactoryMap<Self>;
+    type Widgets = VimGridWidgets;
+    type Root = VimGridView;
+    type View = gtk::Fixed;
+    type Msg = app::AppMessage;
+
+    fn init_view(&self, grid: &u64, sender: Sender<app::AppMessage>) -> VimGridWidgets {
+        let grid = *grid;
+        view! {
+            view = VimGridView::new(grid, self.width as _, self.height as _) {
+                set_widget_name: &format!("vim-grid-{}-{}", self.win, grid),
+                set_textbuf: self.textbuf.clone(),
+
+                set_visible: self.visible,
+                set_can_focus: true,
+                set_focusable: true,
+                set_focus_on_click: true,
+
+                set_overflow: gtk::Overflow::Hidden,
+
+                set_font_description: &self.font_description.borrow(),
+
+                set_css_classes: &["vim-view-grid", &format!("vim-view-grid-{}", self.grid)],
+            }
+        }
+
+        self.connect_pointer(&view, grid, &sender);
+
+        VimGridWidgets {
+            view,
+            external: RefCell::new(None),
+            sender,
+        }
     }
 
     fn position(&self, _: &u64) -> FixedPosition {
@@ -313,7 +756,36 @@ impl factory::FactoryPrototype for VimGrid {
             self.width,
             self.height
         );
-        let view = &widgets.view;
+        if self.external && widgets.external.borrow().is_none() {
+            let external = self.external_window(*index, widgets);
+            widgets.external.replace(Some(external));
+        } else if !self.external {
+            if let Some(external) = widgets.external.take() {
+                external.destroy();
+            }
+        }
+        let external = widgets.external.borrow();
+        if let Some(ExternalWindow {
+            ref window,
+            ref overlay,
+            ..
+        }) = *external
+        {
+            widgets.view.set_visible(false);
+            window.set_visible(self.visible);
+            if let Some(ref host) = self.cursor_host {
+                if self.cursor.is_some() {
+                    host.attach(overlay);
+                } else {
+                    host.detach(overlay);
+                }
+            }
+        }
+        // content goes to the toplevel of external window.
+        let view = match *external {
+            Some(ExternalWindow { ref view, .. }) => view,
+            None => &widgets.view,
+        };
 
         view.set_visible(self.visible);
         view.set_font_description(&self.font_description.borrow());
@@ -322,18 +794,57 @@ impl factory::FactoryPrototype for VimGrid {
         let p_height = view.property::<u64>("height") as usize;
         if self.width != p_width || self.height != p_height {
             view.resize(self.width as _, self.height as _);
+            if let Some(ExternalWindow { ref window, .. }) = *external {
+                let metrics = self.metrics.get();
+                window.set_default_size(
+                    (self.width as f64 * metrics.width()).ceil() as i32,
+                    (self.height as f64 * metrics.height()).ceil() as i32,
+                );
+            }
+        }
+
+        let scrolls = self.scrolls.take();
+        if !scrolls.is_empty() {
+            let settings = SETTINGS.get::<ScrollSettings>();
+            let duration = if scheduler().is_paused() {
+                0
+            } else {
+                (settings.animation_length.max(0.) * 1000.) as u32
+            };
+            for Scrolled {
+                top,
+                bottom,
+                rows,
+                lines,
+            } in scrolls
+            {
+                view.scroll(top, bottom, rows, lines, duration);
+            }
+        }
+
+        view.set_viewport(self.viewport.filter(|_| !self.is_float));
+        view.set_cursor(self.cursor);
+
+        if external.is_some() {
+            view.queue_resize();
+            view.queue_draw();
+            return;
         }
 
         view.set_focusable(self.focusable);
         view.set_is_float(self.is_float);
+        view.set_stacking(self.zindex, self.order);
+        // gtk picks the grid below instead, with pointer in its coordinates.
+        view.set_can_target(self.focusable || !self.is_float);
 
         if let Some(pos) = self.move_to.take() {
-            gtk::prelude::FixedExt::move_(
-                &view.parent().unwrap().downcast::<gtk::Fixed>().unwrap(),
-                view,
-                pos.x,
-                pos.y,
-            );
+            let duration = if self.transit.take() && !scheduler().is_paused() {
+                let settings = SETTINGS.get::<WindowSettings>();
+                (settings.animation_length.max(0.) * 1000.) as u32
+            } else {
+                0
+            };
+            view.transition(pos.x, pos.y, duration);
         }
 
         view.queue_allocate();